@@ -0,0 +1,263 @@
+use std::{collections::HashMap, fs::File, io::{Read, Write}, path::{Path, PathBuf}};
+use serde::{Deserialize, Serialize};
+use crate::targets::{self, Target};
+
+///pack files into the archive before removing them
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct Archive
+{
+    ///directory for created archives
+    pub destination: PathBuf,
+    ///archive name template without extension, supports `{name}`, `{date}` and `{time}`
+    #[serde(default = "default_name")]
+    pub name: String,
+    #[serde(default)]
+    pub format: ArchiveFormat
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ArchiveFormat
+{
+    #[default]
+    #[serde(rename = "tar.zst")]
+    TarZst,
+    #[serde(rename = "zip")]
+    Zip
+}
+impl ArchiveFormat
+{
+    fn extension(&self) -> &'static str
+    {
+        match self
+        {
+            ArchiveFormat::TarZst => "tar.zst",
+            ArchiveFormat::Zip => "zip"
+        }
+    }
+}
+
+///sizes and BLAKE3 digests of the packed files by their names inside the archive
+type Digests = HashMap<String, (u64, blake3::Hash)>;
+
+fn default_name() -> String
+{
+    "{name}_{date}_{time}".to_owned()
+}
+
+impl Archive
+{
+    ///archives inside of the task path would be removed by the same task
+    pub fn check_destination(&self, path: &Path) -> Result<(), String>
+    {
+        let destination = std::path::absolute(&self.destination).unwrap_or(self.destination.clone());
+        let path = std::path::absolute(path).unwrap_or(path.to_path_buf());
+        if destination.starts_with(&path)
+        {
+            Err(["Директория архивов `", &destination.to_string_lossy(), "` находится внутри `", &path.to_string_lossy(), "` и будет удалена задачей"].concat())
+        }
+        else
+        {
+            Ok(())
+        }
+    }
+    ///archive path for the task path, name template is filled with the current date
+    fn archive_path(&self, path: &Path) -> PathBuf
+    {
+        let now = utilites::Date::now();
+        let now = now.as_naive_datetime();
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("deltime");
+        let base = self.name
+            .replace("{name}", name)
+            .replace("{date}", &now.format("%Y-%m-%d").to_string())
+            .replace("{time}", &now.format("%H-%M-%S").to_string());
+        let mut archive = self.destination.join([&base, ".", self.format.extension()].concat());
+        let mut n = 1;
+        while archive.exists()
+        {
+            archive = self.destination.join([&base, "_", &n.to_string(), ".", self.format.extension()].concat());
+            n += 1;
+        }
+        archive
+    }
+}
+
+///pack targets into the archive and verify it, names inside the archive are relative to the parent of `root`
+pub async fn pack(settings: &Archive, root: &Path, targets: &[Target]) -> std::io::Result<PathBuf>
+{
+    let base = root.parent().unwrap_or(root).to_path_buf();
+    //links are not archived, the files they point to can be outside of the task directory
+    let entries: Vec<Target> = targets::expand(targets).into_iter().filter(|e| !e.is_link).collect();
+    let archive = settings.archive_path(root);
+    let format = settings.format;
+    let destination = settings.destination.clone();
+    let archive_path = archive.clone();
+    tokio::task::spawn_blocking(move ||
+    {
+        std::fs::create_dir_all(&destination)?;
+        let written = match format
+        {
+            ArchiveFormat::TarZst => pack_tar_zst(&archive_path, &base, &entries),
+            ArchiveFormat::Zip => pack_zip(&archive_path, &base, &entries)
+        };
+        let verified = written.and_then(|expected|
+        {
+            let actual = match format
+            {
+                ArchiveFormat::TarZst => read_tar_zst(&archive_path),
+                ArchiveFormat::Zip => read_zip(&archive_path)
+            }?;
+            if expected.iter().all(|(name, digest)| actual.get(name) == Some(digest))
+            {
+                Ok(())
+            }
+            else
+            {
+                Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "содержимое архива не совпадает с исходными файлами"))
+            }
+        });
+        if verified.is_err()
+        {
+            let _ = std::fs::remove_file(&archive_path);
+        }
+        verified
+    })
+    .await
+    .map_err(std::io::Error::other)??;
+    Ok(archive)
+}
+
+fn entry_name(base: &Path, path: &Path) -> String
+{
+    path.strip_prefix(base).unwrap_or(path).to_string_lossy().replace('\\', "/")
+}
+
+///reader computing BLAKE3 of the data passed into the archive
+struct HashingReader<R>
+{
+    inner: R,
+    hasher: blake3::Hasher,
+    len: u64
+}
+impl<R: Read> HashingReader<R>
+{
+    fn new(inner: R) -> Self
+    {
+        Self
+        {
+            inner,
+            hasher: blake3::Hasher::new(),
+            len: 0
+        }
+    }
+    fn digest(&self) -> (u64, blake3::Hash)
+    {
+        (self.len, self.hasher.finalize())
+    }
+}
+impl<R: Read> Read for HashingReader<R>
+{
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize>
+    {
+        let n = self.inner.read(buf)?;
+        self.hasher.update(&buf[..n]);
+        self.len += n as u64;
+        Ok(n)
+    }
+}
+
+///size and BLAKE3 of the data read from the archive entry
+fn digest_of<R: Read>(entry: &mut R) -> std::io::Result<(u64, blake3::Hash)>
+{
+    let mut hasher = blake3::Hasher::new();
+    let size = std::io::copy(entry, &mut hasher)?;
+    Ok((size, hasher.finalize()))
+}
+
+///returns sizes and digests of the packed files, computed from the data written into the archive
+fn pack_tar_zst(archive: &Path, base: &Path, entries: &[Target]) -> std::io::Result<Digests>
+{
+    let mut expected = HashMap::new();
+    let encoder = zstd::Encoder::new(File::create_new(archive)?, 0)?;
+    let mut builder = tar::Builder::new(encoder);
+    for e in entries
+    {
+        let name = entry_name(base, &e.path);
+        if e.is_dir
+        {
+            builder.append_dir(&name, &e.path)?;
+        }
+        else
+        {
+            let file = File::open(&e.path)?;
+            let mut header = tar::Header::new_gnu();
+            header.set_metadata(&file.metadata()?);
+            let mut reader = HashingReader::new(file);
+            builder.append_data(&mut header, &name, &mut reader)?;
+            expected.insert(name, reader.digest());
+        }
+    }
+    let file = builder.into_inner()?.finish()?;
+    file.sync_all()?;
+    Ok(expected)
+}
+
+fn read_tar_zst(archive: &Path) -> std::io::Result<Digests>
+{
+    let mut actual = HashMap::new();
+    let mut tar = tar::Archive::new(zstd::Decoder::new(File::open(archive)?)?);
+    for entry in tar.entries()?
+    {
+        let mut entry = entry?;
+        if entry.header().entry_type().is_file()
+        {
+            let name = entry.path()?.to_string_lossy().replace('\\', "/");
+            actual.insert(name, digest_of(&mut entry)?);
+        }
+    }
+    Ok(actual)
+}
+
+fn pack_zip(archive: &Path, base: &Path, entries: &[Target]) -> std::io::Result<Digests>
+{
+    let mut expected = HashMap::new();
+    let mut zip = zip::ZipWriter::new(File::create_new(archive)?);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated)
+        .large_file(true);
+    for e in entries
+    {
+        let name = entry_name(base, &e.path);
+        if e.is_dir
+        {
+            zip.add_directory(name, options).map_err(std::io::Error::other)?;
+        }
+        else
+        {
+            let mut reader = HashingReader::new(File::open(&e.path)?);
+            zip.start_file(name.as_str(), options).map_err(std::io::Error::other)?;
+            std::io::copy(&mut reader, &mut zip)?;
+            expected.insert(name, reader.digest());
+        }
+    }
+    let mut file = zip.finish().map_err(std::io::Error::other)?;
+    file.flush()?;
+    file.sync_all()?;
+    Ok(expected)
+}
+
+///reading every entry to the end checks its crc
+fn read_zip(archive: &Path) -> std::io::Result<Digests>
+{
+    let mut actual = HashMap::new();
+    let mut zip = zip::ZipArchive::new(File::open(archive)?).map_err(std::io::Error::other)?;
+    for i in 0..zip.len()
+    {
+        let mut entry = zip.by_index(i).map_err(std::io::Error::other)?;
+        if entry.is_file()
+        {
+            let name = entry.name().to_owned();
+            actual.insert(name, digest_of(&mut entry)?);
+        }
+    }
+    Ok(actual)
+}
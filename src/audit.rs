@@ -0,0 +1,321 @@
+use std::{path::{Path, PathBuf}, sync::OnceLock, time::UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use tokio::{io::{AsyncBufReadExt, AsyncWriteExt}, sync::Mutex};
+use utilites::Date;
+use crate::{rotate::{self, Rotate}, targets::Target};
+
+///audit log of processed entries in JSON Lines format
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct Audit
+{
+    #[serde(default = "default_path")]
+    pub path: PathBuf,
+    ///log is rotated when its size exceeds `max_size`
+    #[serde(default = "default_max_size")]
+    #[serde(with = "crate::helpers::size")]
+    pub max_size: u64,
+    ///count of kept rotated logs
+    #[serde(default = "default_keep")]
+    pub keep: u32,
+    ///compute BLAKE3 digest of every file before processing
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub digest: bool
+}
+impl Default for Audit
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            path: default_path(),
+            max_size: default_max_size(),
+            keep: default_keep(),
+            digest: false
+        }
+    }
+}
+
+fn default_path() -> PathBuf
+{
+    PathBuf::from("audit.jsonl")
+}
+fn default_max_size() -> u64
+{
+    10 * 1024 * 1024
+}
+fn default_keep() -> u32
+{
+    5
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome
+{
+    Ok,
+    Error
+}
+
+///one processed entry
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct AuditEntry
+{
+    ///local time of processing `2025-02-15T21:33:44`
+    pub time: String,
+    pub task: String,
+    pub path: PathBuf,
+    pub size: u64,
+    ///modification time in seconds since unix epoch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mtime: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blake3: Option<String>,
+    ///`delete`, `trash`, `shred`, `truncate` or `rotate`
+    pub mode: String,
+    pub outcome: Outcome,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>
+}
+
+impl AuditEntry
+{
+    ///entry for the target with metadata collected before processing
+    pub async fn new(target: &Target, task: &str, mode: &str, digest: bool) -> Self
+    {
+        let mtime = tokio::fs::symlink_metadata(&target.path).await
+            .and_then(|md| md.modified())
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs());
+        let blake3 = if digest && !target.is_dir && !target.is_link
+        {
+            crate::verify::hash_file(&target.path).await.ok()
+        }
+        else
+        {
+            None
+        };
+        Self
+        {
+            time: now(),
+            task: task.to_owned(),
+            path: target.path.clone(),
+            size: target.size,
+            mtime,
+            blake3,
+            mode: mode.to_owned(),
+            outcome: Outcome::Ok,
+            reason: None
+        }
+    }
+    pub fn failed(mut self, reason: &str) -> Self
+    {
+        self.outcome = Outcome::Error;
+        self.reason = Some(reason.to_owned());
+        self
+    }
+}
+
+fn now() -> String
+{
+    Date::now().as_naive_datetime().format("%Y-%m-%dT%H:%M:%S").to_string()
+}
+
+struct AuditLog
+{
+    settings: Audit,
+    lock: Mutex<()>
+}
+
+static AUDIT: OnceLock<AuditLog> = OnceLock::new();
+
+///enable audit log for all tasks, settings are taken only from the local config
+pub fn init(settings: Option<Audit>)
+{
+    if let Some(settings) = settings
+    {
+        let _ = AUDIT.set(AuditLog { settings, lock: Mutex::new(()) });
+    }
+}
+
+pub fn is_enabled() -> bool
+{
+    AUDIT.get().is_some()
+}
+
+pub fn with_digest() -> bool
+{
+    AUDIT.get().is_some_and(|a| a.settings.digest)
+}
+
+///append the entry to the audit log
+pub async fn record(entry: &AuditEntry)
+{
+    let Some(audit) = AUDIT.get() else
+    {
+        return;
+    };
+    let _lock = audit.lock.lock().await;
+    if let Err(e) = write(&audit.settings, entry).await
+    {
+        logger::error!("Ошибка записи журнала аудита `{}`: {}", audit.settings.path.display(), e);
+    }
+}
+
+async fn write(settings: &Audit, entry: &AuditEntry) -> std::io::Result<()>
+{
+    let size = tokio::fs::metadata(&settings.path).await.map(|md| md.len()).unwrap_or(0);
+    if size > 0 && size >= settings.max_size
+    {
+        let rotate = Rotate
+        {
+            keep: settings.keep,
+            compress: None,
+            create: false
+        };
+        rotate::rotate_file(&settings.path, &rotate).await?;
+    }
+    let mut line = serde_json::to_string(entry).map_err(std::io::Error::other)?;
+    line.push('\n');
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&settings.path)
+        .await?;
+    file.write_all(line.as_bytes()).await?;
+    file.flush().await
+}
+
+///filter of the audit entries
+#[derive(Debug, Default)]
+pub struct AuditFilter
+{
+    pub task: Option<String>,
+    pub path_prefix: Option<PathBuf>,
+    pub from: Option<Date>,
+    pub to: Option<Date>
+}
+
+impl AuditFilter
+{
+    ///`--task <id> --path <prefix> --from <date> --to <date>`
+    pub fn from_args(args: &[String]) -> Result<Self, String>
+    {
+        let mut filter = Self::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next()
+        {
+            let value = args.next().ok_or_else(|| ["Не указано значение аргумента ", arg].concat())?;
+            match arg.as_str()
+            {
+                "--task" => filter.task = Some(value.clone()),
+                "--path" => filter.path_prefix = Some(PathBuf::from(value)),
+                "--from" => filter.from = Some(parse_date(value, "T00:00:00")?),
+                "--to" => filter.to = Some(parse_date(value, "T23:59:59")?),
+                _ => return Err(["Неизвестный аргумент ", arg].concat())
+            }
+        }
+        Ok(filter)
+    }
+
+    pub fn matches(&self, entry: &AuditEntry) -> bool
+    {
+        if self.task.as_ref().is_some_and(|t| *t != entry.task)
+        {
+            return false;
+        }
+        if self.path_prefix.as_ref().is_some_and(|p| !entry.path.starts_with(p))
+        {
+            return false;
+        }
+        if self.from.is_none() && self.to.is_none()
+        {
+            return true;
+        }
+        let Some(time) = Date::parse(&entry.time).map(|d| d.as_naive_datetime()) else
+        {
+            return false;
+        };
+        self.from.as_ref().is_none_or(|f| time >= f.as_naive_datetime())
+        && self.to.as_ref().is_none_or(|t| time <= t.as_naive_datetime())
+    }
+}
+
+///date or date with time, `2025-02-15` is completed by `time`, the start or the end of the day
+fn parse_date(value: &str, time: &str) -> Result<Date, String>
+{
+    Date::parse(value)
+        .or_else(|| Date::parse(&[value, time].concat()))
+        .ok_or_else(|| ["Ошибка формата даты ", value].concat())
+}
+
+///read entries from the audit log and its rotated copies, the oldest go first
+pub async fn read(path: &Path, filter: &AuditFilter) -> std::io::Result<Vec<AuditEntry>>
+{
+    let mut files = Vec::new();
+    let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    for n in 1..
+    {
+        let generation = path.with_file_name([name.as_str(), ".", &n.to_string()].concat());
+        if !generation.exists()
+        {
+            break;
+        }
+        files.push(generation);
+    }
+    files.reverse();
+    files.push(path.to_path_buf());
+    let mut entries = Vec::new();
+    for f in files
+    {
+        let file = match tokio::fs::File::open(&f).await
+        {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e)
+        };
+        let mut lines = tokio::io::BufReader::new(file).lines();
+        while let Some(line) = lines.next_line().await?
+        {
+            if line.trim().is_empty()
+            {
+                continue;
+            }
+            match serde_json::from_str::<AuditEntry>(&line)
+            {
+                Ok(e) if filter.matches(&e) => entries.push(e),
+                Ok(_) => (),
+                Err(e) => logger::warn!("Некорректная запись журнала аудита `{}`: {}", f.display(), e)
+            }
+        }
+    }
+    Ok(entries)
+}
+
+///`deltime audit [--task <id>] [--path <prefix>] [--from <date>] [--to <date>]`, prints filtered entries
+pub async fn print(path: &Path, args: &[String])
+{
+    let filter = match AuditFilter::from_args(args)
+    {
+        Ok(filter) => filter,
+        Err(e) =>
+        {
+            eprintln!("{}", e);
+            return;
+        }
+    };
+    match read(path, &filter).await
+    {
+        Ok(entries) =>
+        {
+            for e in entries
+            {
+                if let Ok(line) = serde_json::to_string(&e)
+                {
+                    println!("{}", line);
+                }
+            }
+        },
+        Err(e) => eprintln!("Ошибка чтения журнала аудита `{}`: {}", path.display(), e)
+    }
+}
@@ -1,35 +1,49 @@
-use std::{collections::HashMap, path::Path, sync::Arc};
+use std::{collections::HashMap, path::{Path, PathBuf}, sync::Arc};
 
+use chrono_tz::Tz;
 use indicatif::MultiProgress;
 use scheduler::Scheduler;
 use serde::{Deserialize, Serialize};
 use tokio::sync::RwLock;
 
-use crate::{beeper, structs::{Task, TaskWithProgress}};
+use crate::{audit::Audit, beeper, guard::Guard, zone, structs::{Task, TaskWithProgress}};
 
 pub const FILE_NAME: &str = "config.toml";
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
 pub struct Config 
 {
+    ///switch all tasks from this config into dry-run mode
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub dry_run: bool,
+    ///tasks are allowed only inside of these directories, used only from the local config
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_roots: Vec<PathBuf>,
+    ///allow tasks from usb configs outside of `allowed_roots` (or any tasks if `allowed_roots` is empty)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub usb_unrestricted: bool,
+    ///audit log of processed entries, used only from the local config
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audit: Option<Audit>,
+    ///time zone of tasks without their own `timezone`
+    #[serde(deserialize_with="zone::deserialize_timezone", serialize_with="zone::serialize_timezone")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<Tz>,
     pub tasks: Vec<Task>
 }
 impl Config
 {
     pub async fn load() -> Self
     {
-        if let Ok(config) = Config::load_local()
+        match Config::load_local()
         {
-            config
-        }
-        else 
-        {
-            logger::warn!("Локальный файл конфигурации {} не обнаружен, ожидаю ввода...", FILE_NAME);
-            #[cfg(feature="beeper")]
-            beeper::Beeper::ok().await;
-            Config
+            Ok(config) => config,
+            Err(e) =>
             {
-                tasks: Vec::new()
+                logger::warn!("Локальный файл конфигурации {} не загружен ({}), ожидаю ввода...", FILE_NAME, e);
+                #[cfg(feature="beeper")]
+                beeper::Beeper::ok().await;
+                Config::default()
             }
         }
     }
@@ -46,15 +60,49 @@ impl Config
         Ok(config)
     }
 
-    pub async fn add_tasks(self, mpb: MultiProgress, tasks: Arc<RwLock<HashMap<Arc<String>, TaskWithProgress>>>, scheduler: Scheduler<Arc<String>>)
+    ///guard for tasks from the local config
+    pub fn guard(&self) -> Guard
+    {
+        Guard::new(self.allowed_roots.clone(), false)
+    }
+    ///guard for tasks from usb configs, `usb_unrestricted` removes only the restriction to `allowed_roots`
+    pub fn usb_guard(&self) -> Guard
+    {
+        if self.usb_unrestricted
+        {
+            Guard::new(Vec::new(), false)
+        }
+        else 
+        {
+            Guard::new(self.allowed_roots.clone(), true).without_hooks()
+        }
+    }
+
+    pub async fn add_tasks(self, mpb: MultiProgress, tasks: Arc<RwLock<HashMap<Arc<String>, TaskWithProgress>>>, scheduler: Scheduler<Arc<String>>, guard: &Guard)
     {
         #[cfg(feature="beeper")]
         super::beeper::Beeper::ok().await;
-        for task in self.tasks.into_iter()
+        for mut task in self.tasks.into_iter()
         {
+            if self.dry_run
+            {
+                task.dry_run = true;
+            }
+            if task.timezone.is_none()
+            {
+                task.timezone = self.timezone;
+            }
+            task.localize();
+            let valid = task.validate().and(guard.check_hooks(task.before.is_some() || task.after.is_some()));
             let task_id = Arc::new(task.get_hash());
-            let task = TaskWithProgress::new(task, &mpb);
+            let task = TaskWithProgress::new(task, &mpb, guard.clone());
             logger::debug!("new task fom config: {:?} id: {}", &task, &task_id);
+            if let Err(e) = guard.check(task.get_path()).and(valid)
+            {
+                logger::error!("{}", &e);
+                task.finish_with_err(e);
+                continue;
+            }
             let exists = 
             {
                 let guard = tasks.read().await;
@@ -62,12 +110,28 @@ impl Config
             };
             if !exists
             {
-                let repeating = *task.get_strategy();
+                let repeating = task.get_strategy();
                 if task.path_is_exists()
                 {
-                    if let Some(i) = task.get_interval()
+                    if task.get_free_space_threshold().is_some()
+                    {
+                        {
+                            let mut guard = tasks.write().await;
+                            guard.insert(task_id.clone(), task);
+                        }
+                        crate::tasker::watch_free_space(task_id, tasks.clone());
+                    }
+                    else if task.is_self_scheduled()
+                    {
+                        {
+                            let mut guard = tasks.write().await;
+                            guard.insert(task_id.clone(), task);
+                        }
+                        crate::tasker::watch_schedule(task_id, tasks.clone());
+                    }
+                    else if let Some(i) = task.get_interval()
                     {
-                        if scheduler.add_interval_task(task_id.clone(), i, repeating).await
+                        if scheduler.add_interval_task(task_id.clone(), (i.as_secs() / 60) as u32, repeating).await
                         {
                             let mut guard = tasks.write().await;
                             guard.insert(task_id, task);
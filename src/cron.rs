@@ -0,0 +1,192 @@
+use chrono::{Datelike, NaiveDate, NaiveDateTime, TimeDelta, Timelike};
+use serde::{Deserialize, Serialize, Serializer};
+
+const MONTHS: [&str; 12] = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+pub const WEEKDAYS: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+///cron expression `minute hour day month weekday`, e.g. `30 2 * * 1-5` or `*/15 8-17 * * *`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cron
+{
+    expression: String,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    ///day or weekday field is `*`, otherwise the day matches any of them as in vixie cron
+    any_day: bool
+}
+
+impl Cron
+{
+    pub fn parse(expression: &str) -> Result<Self, String>
+    {
+        let expression = expression.trim();
+        let expanded = match expression
+        {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            e => e
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let &[minute, hour, day, month, weekday] = fields.as_slice() else
+        {
+            return Err(["выражение `", expression, "` должно содержать 5 полей"].concat());
+        };
+        let mut weekdays = parse_field(weekday, 0, 7, &WEEKDAYS)?;
+        //sunday is both 0 and 7
+        if weekdays & (1 << 7) != 0
+        {
+            weekdays |= 1;
+        }
+        Ok(Self
+        {
+            expression: expression.to_owned(),
+            minutes: parse_field(minute, 0, 59, &[])?,
+            hours: parse_field(hour, 0, 23, &[])?,
+            days: parse_field(day, 1, 31, &[])?,
+            months: parse_field(month, 1, 12, &MONTHS)?,
+            weekdays,
+            any_day: day.starts_with('*') || weekday.starts_with('*')
+        })
+    }
+
+    ///the nearest time matching the expression strictly after `after`, none if it isn't found within 5 years
+    pub fn next_after(&self, after: &NaiveDateTime) -> Option<NaiveDateTime>
+    {
+        let mut t = after.date().and_hms_opt(after.hour(), after.minute(), 0)? + TimeDelta::minutes(1);
+        let limit = after.year() + 5;
+        while t.year() <= limit
+        {
+            if !bit(self.months, t.month())
+            {
+                let (year, month) = if t.month() == 12 { (t.year() + 1, 1) } else { (t.year(), t.month() + 1) };
+                t = NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)?;
+            }
+            else if !self.day_matches(&t.date())
+            {
+                t = t.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+            }
+            else if !bit(self.hours, t.hour())
+            {
+                t = t.date().and_hms_opt(t.hour(), 0, 0)? + TimeDelta::hours(1);
+            }
+            else if !bit(self.minutes, t.minute())
+            {
+                t += TimeDelta::minutes(1);
+            }
+            else
+            {
+                return Some(t);
+            }
+        }
+        None
+    }
+
+    fn day_matches(&self, date: &NaiveDate) -> bool
+    {
+        let day = bit(self.days, date.day());
+        let weekday = bit(self.weekdays, date.weekday().num_days_from_sunday());
+        if self.any_day
+        {
+            day && weekday
+        }
+        else
+        {
+            day || weekday
+        }
+    }
+}
+
+fn bit(mask: u64, value: u32) -> bool
+{
+    mask & (1 << value) != 0
+}
+
+///`*`, `5`, `1-5`, `*/15`, `8-18/2`, `mon`, lists separated by comma
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str]) -> Result<u64, String>
+{
+    let mut mask = 0;
+    for part in field.split(',')
+    {
+        let (range, step) = match part.split_once('/')
+        {
+            Some((range, step)) =>
+            {
+                let step = step.parse::<u32>().ok().filter(|s| *s > 0)
+                    .ok_or_else(|| ["некорректный шаг в `", field, "`"].concat())?;
+                (range, Some(step))
+            },
+            None => (part, None)
+        };
+        let (from, to) = if range == "*"
+        {
+            (min, max)
+        }
+        else if let Some((from, to)) = range.split_once('-')
+        {
+            (parse_value(from, min, max, names)?, parse_value(to, min, max, names)?)
+        }
+        else
+        {
+            let value = parse_value(range, min, max, names)?;
+            (value, if step.is_some() { max } else { value })
+        };
+        if from > to
+        {
+            return Err(["некорректный диапазон в `", field, "`"].concat());
+        }
+        for v in (from..=to).step_by(step.unwrap_or(1) as usize)
+        {
+            mask |= 1 << v;
+        }
+    }
+    Ok(mask)
+}
+
+fn parse_value(value: &str, min: u32, max: u32, names: &[&str]) -> Result<u32, String>
+{
+    let lower = value.to_ascii_lowercase();
+    let parsed = names.iter()
+        .position(|n| *n == lower)
+        .map(|p| p as u32 + min)
+        .or_else(|| value.parse::<u32>().ok())
+        .filter(|v| (min..=max).contains(v));
+    parsed.ok_or_else(|| ["значение `", value, "` вне диапазона ", &min.to_string(), "-", &max.to_string()].concat())
+}
+
+impl std::fmt::Display for Cron
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        f.write_str(&self.expression)
+    }
+}
+impl<'de> Deserialize<'de> for Cron
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::de::Deserializer<'de>,
+    {
+        let s: String = serde::de::Deserialize::deserialize(deserializer)?;
+        let cron = Cron::parse(&s).map_err(|e| serde::de::Error::custom(["Ошибка формата cron: ", &e].concat()))?;
+        if cron.next_after(&utilites::Date::now().as_naive_datetime()).is_none()
+        {
+            return Err(serde::de::Error::custom(["Выражение cron `", &s, "` никогда не срабатывает"].concat()));
+        }
+        Ok(cron)
+    }
+}
+impl Serialize for Cron
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.expression)
+    }
+}
@@ -0,0 +1,289 @@
+use std::{fs::Metadata, path::Path, time::{Duration, SystemTime}};
+use glob::{MatchOptions, Pattern, PatternError};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use crate::targets::Target;
+
+///file timestamp used for age filtering
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeKind
+{
+    ///last modification
+    #[default]
+    Mtime,
+    ///last status change (creation time on windows)
+    Ctime,
+    ///last access
+    Atime
+}
+impl TimeKind
+{
+    pub fn is_default(&self) -> bool
+    {
+        *self == TimeKind::default()
+    }
+}
+
+///order of files for retention, the last one is the newest
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum KeepBy
+{
+    ///modification time
+    #[default]
+    Mtime,
+    ///file name, useful for names with dates like `backup_2025-02-15.bak`
+    Name
+}
+impl KeepBy
+{
+    pub fn is_default(&self) -> bool
+    {
+        *self == KeepBy::default()
+    }
+}
+
+///what to do with symbolic links
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SymlinkPolicy
+{
+    ///remove the link itself, links are never traversed
+    #[default]
+    DeleteLink,
+    ///leave links untouched
+    Skip,
+    ///process the file the link points to, only if it is inside of the task directory
+    Follow
+}
+impl SymlinkPolicy
+{
+    pub fn is_default(&self) -> bool
+    {
+        *self == SymlinkPolicy::default()
+    }
+}
+
+///apply symlink policy to the selected file, returns target for processing or the reason of skipping
+pub fn apply_symlink_policy(target: Target, root: &Path, policy: SymlinkPolicy) -> Result<Target, &'static str>
+{
+    if !target.is_link
+    {
+        return Ok(target);
+    }
+    match policy
+    {
+        SymlinkPolicy::DeleteLink => Ok(target),
+        SymlinkPolicy::Skip => Err("символическая ссылка"),
+        SymlinkPolicy::Follow =>
+        {
+            let resolved = std::fs::canonicalize(&target.path).map_err(|_| "битая символическая ссылка")?;
+            let root = std::fs::canonicalize(root).map_err(|_| "ошибка получения пути директории задачи")?;
+            if !resolved.starts_with(&root)
+            {
+                return Err("символическая ссылка указывает за пределы директории задачи");
+            }
+            match follow_link(&resolved)
+            {
+                Ok(t) if !t.is_dir => Ok(t),
+                _ => Err("символическая ссылка указывает не на файл")
+            }
+        }
+    }
+}
+
+///target for the file or directory the link points to
+pub fn follow_link(path: &Path) -> Result<Target, &'static str>
+{
+    let resolved = std::fs::canonicalize(path).map_err(|_| "битая символическая ссылка")?;
+    match std::fs::metadata(&resolved)
+    {
+        Ok(md) if md.is_file() => Ok(Target::file(resolved, md.len())),
+        Ok(md) if md.is_dir() => Ok(Target::dir(resolved)),
+        _ => Err("символическая ссылка указывает не на файл или директорию")
+    }
+}
+
+pub fn file_time(md: &Metadata, kind: TimeKind) -> Option<SystemTime>
+{
+    match kind
+    {
+        TimeKind::Mtime => md.modified().ok(),
+        TimeKind::Atime => md.accessed().ok(),
+        #[cfg(unix)]
+        TimeKind::Ctime =>
+        {
+            use std::os::unix::fs::MetadataExt;
+            let secs = md.ctime();
+            if secs >= 0
+            {
+                Some(SystemTime::UNIX_EPOCH + Duration::new(secs as u64, md.ctime_nsec() as u32))
+            }
+            else 
+            {
+                None
+            }
+        },
+        #[cfg(not(unix))]
+        TimeKind::Ctime => md.created().ok()
+    }
+}
+
+///file age is between `newer_than` and `older_than`, files without timestamp are not matched
+pub fn is_aged(path: &Path, kind: TimeKind, older_than: Option<Duration>, newer_than: Option<Duration>) -> bool
+{
+    let age = std::fs::symlink_metadata(path)
+        .ok()
+        .and_then(|md| file_time(&md, kind))
+        .and_then(|t| SystemTime::now().duration_since(t).ok().or(Some(Duration::ZERO)));
+    if let Some(age) = age
+    {
+        older_than.is_none_or(|o| age >= o) && newer_than.is_none_or(|n| age < n)
+    }
+    else 
+    {
+        false
+    }
+}
+
+///the oldest `candidates` which should be removed so that `usage` files fit under `max_size` and `max_files`
+pub fn over_quota(usage: &[Target], candidates: Vec<Target>, max_size: Option<u64>, max_files: Option<u64>) -> Vec<Target>
+{
+    let mut size: u64 = usage.iter().map(|t| t.size).sum();
+    let mut count = usage.len() as u64;
+    let mut candidates: Vec<(Option<SystemTime>, Target)> = candidates
+        .into_iter()
+        .map(|t| (std::fs::symlink_metadata(&t.path).ok().and_then(|md| file_time(&md, TimeKind::Mtime)), t))
+        .collect();
+    candidates.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut selected = Vec::new();
+    for (_, t) in candidates
+    {
+        let size_ok = max_size.is_none_or(|m| size <= m);
+        let count_ok = max_files.is_none_or(|m| count <= m);
+        if size_ok && count_ok
+        {
+            break;
+        }
+        size = size.saturating_sub(t.size);
+        count = count.saturating_sub(1);
+        selected.push(t);
+    }
+    selected
+}
+
+///all files except the newest `keep`
+pub fn keep_last(files: Vec<Target>, keep: usize, by: KeepBy) -> Vec<Target>
+{
+    let mut files = files;
+    match by
+    {
+        KeepBy::Mtime =>
+        {
+            let mut with_time: Vec<(Option<SystemTime>, Target)> = files
+                .into_iter()
+                .map(|t| (std::fs::symlink_metadata(&t.path).ok().and_then(|md| file_time(&md, TimeKind::Mtime)), t))
+                .collect();
+            with_time.sort_by(|a, b| a.0.cmp(&b.0));
+            files = with_time.into_iter().map(|(_, t)| t).collect();
+        },
+        KeepBy::Name => files.sort_by(|a, b| a.path.file_name().cmp(&b.path.file_name()))
+    }
+    let len = files.len().saturating_sub(keep);
+    files.truncate(len);
+    files
+}
+
+const GLOB_OPTIONS: MatchOptions = MatchOptions
+{
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false
+};
+
+///include/exclude glob lists matched against paths relative to the task directory,
+///patterns without `/` are matched against file name at any depth
+pub struct Globs
+{
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+    ///`mask` matches only files in the task directory itself
+    mask: Option<Pattern>
+}
+impl Globs
+{
+    pub fn new(include: &[String], exclude: &[String], mask: Option<&String>) -> Result<Self, PatternError>
+    {
+        Ok(Self
+        {
+            include: include.iter().map(|p| Pattern::new(p)).collect::<Result<_, _>>()?,
+            exclude: exclude.iter().map(|p| Pattern::new(p)).collect::<Result<_, _>>()?,
+            mask: mask.map(|m| Pattern::new(m)).transpose()?
+        })
+    }
+    pub fn is_match(&self, relative: &Path) -> bool
+    {
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        let included = if self.include.is_empty() && self.mask.is_none()
+        {
+            true
+        }
+        else 
+        {
+            self.include.iter().any(|p| Self::pattern_match(p, &relative))
+            || self.mask.as_ref().is_some_and(|m| m.matches_with(&relative, GLOB_OPTIONS))
+        };
+        included && !self.is_excluded(&relative)
+    }
+    ///directory is excluded itself or by pattern for its content like `**/keep/**`
+    pub fn is_dir_excluded(&self, relative: &Path) -> bool
+    {
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        self.is_excluded(&relative) || self.is_excluded(&[&relative, "/"].concat())
+    }
+    pub fn is_excluded(&self, relative: &str) -> bool
+    {
+        self.exclude.iter().any(|p| Self::pattern_match(p, relative))
+    }
+    fn pattern_match(pattern: &Pattern, relative: &str) -> bool
+    {
+        if pattern.as_str().contains('/')
+        {
+            pattern.matches_with(relative, GLOB_OPTIONS)
+        }
+        else 
+        {
+            let name = relative.rsplit('/').next().unwrap_or(relative);
+            pattern.matches_with(name, GLOB_OPTIONS)
+        }
+    }
+}
+
+///part of the path matched by `regex`
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum RegexOn
+{
+    ///file name
+    #[default]
+    Name,
+    ///path relative to the task directory with `/` separators
+    Path
+}
+impl RegexOn
+{
+    pub fn is_default(&self) -> bool
+    {
+        *self == RegexOn::default()
+    }
+}
+
+pub fn regex_match(regex: &Regex, relative: &Path, on: RegexOn) -> bool
+{
+    match on
+    {
+        RegexOn::Name => relative.file_name().is_some_and(|n| regex.is_match(&n.to_string_lossy())),
+        RegexOn::Path => regex.is_match(&relative.to_string_lossy().replace('\\', "/"))
+    }
+}
@@ -0,0 +1,136 @@
+use std::path::Path;
+use serde::{Deserialize, Serialize, Serializer};
+use crate::helpers::{format_size, parse_size};
+
+///free space threshold, absolute `5GiB` or percent of the filesystem size `10%`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Threshold
+{
+    Bytes(u64),
+    Percent(u8)
+}
+
+impl Threshold
+{
+    pub fn parse(value: &str) -> Option<Self>
+    {
+        if let Some(percent) = value.trim().strip_suffix('%')
+        {
+            percent.trim().parse::<u8>().ok()
+                .filter(|p| *p > 0 && *p < 100)
+                .map(Threshold::Percent)
+        }
+        else
+        {
+            parse_size(value).filter(|b| *b > 0).map(Threshold::Bytes)
+        }
+    }
+    ///threshold in bytes for the filesystem with `total` size
+    pub fn limit(&self, total: u64) -> u64
+    {
+        match self
+        {
+            Threshold::Bytes(b) => *b,
+            Threshold::Percent(p) => total / 100 * *p as u64
+        }
+    }
+}
+impl std::fmt::Display for Threshold
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        match self
+        {
+            Threshold::Bytes(b) => f.write_str(&format_size(*b)),
+            Threshold::Percent(p) => write!(f, "{}%", p)
+        }
+    }
+}
+impl<'de> Deserialize<'de> for Threshold
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::de::Deserializer<'de>,
+    {
+        let s: String = serde::de::Deserialize::deserialize(deserializer)?;
+        Threshold::parse(&s).ok_or_else(|| serde::de::Error::custom(["Ошибка формата порога свободного места ", &s].concat()))
+    }
+}
+impl Serialize for Threshold
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+///space of the filesystem available for unprivileged user
+#[derive(Clone, Copy, Debug)]
+pub struct Usage
+{
+    pub free: u64,
+    pub total: u64
+}
+
+#[cfg(target_os = "linux")]
+pub fn usage(path: &Path) -> std::io::Result<Usage>
+{
+    use std::os::unix::ffi::OsStrExt;
+    let c_path = std::ffi::CString::new(path.as_os_str().as_bytes()).map_err(std::io::Error::other)?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0
+    {
+        return Err(std::io::Error::last_os_error());
+    }
+    let block = stat.f_frsize as u64;
+    Ok(Usage
+    {
+        free: stat.f_bavail as u64 * block,
+        total: stat.f_blocks as u64 * block
+    })
+}
+#[cfg(not(target_os = "linux"))]
+pub fn usage(_path: &Path) -> std::io::Result<Usage>
+{
+    Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "проверка свободного места поддерживается только в linux"))
+}
+
+///trigger fires once when free space drops below the threshold
+///and is armed again only after free space rises above the threshold plus 10%
+#[derive(Debug)]
+pub struct Trigger
+{
+    threshold: Threshold,
+    armed: bool
+}
+impl Trigger
+{
+    pub fn new(threshold: Threshold) -> Self
+    {
+        Self
+        {
+            threshold,
+            armed: true
+        }
+    }
+    pub fn check(&mut self, usage: &Usage) -> bool
+    {
+        let limit = self.threshold.limit(usage.total);
+        if usage.free < limit
+        {
+            let fire = self.armed;
+            self.armed = false;
+            fire
+        }
+        else
+        {
+            if usage.free >= limit + limit / 10
+            {
+                self.armed = true;
+            }
+            false
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use std::path::{Path, PathBuf};
+
+///system directories which can't be processed with everything inside
+#[cfg(not(windows))]
+const DENY_TREES: &[&str] = &["/bin", "/boot", "/dev", "/etc", "/lib", "/lib32", "/lib64", "/proc", "/sbin", "/sys", "/usr"];
+///directories which can't be processed themselves, but their content can
+#[cfg(not(windows))]
+const DENY_EXACT: &[&str] = &["/", "/home", "/root", "/var", "/var/lib", "/var/log", "/var/cache", "/opt", "/run", "/tmp", "/mnt", "/media", "/srv"];
+///directories of users homes, every home inside can't be processed itself
+#[cfg(not(windows))]
+const HOMES: &[&str] = &["/home"];
+#[cfg(windows)]
+const DENY_TREES: &[&str] = &["C:\\Windows", "C:\\Program Files", "C:\\Program Files (x86)", "C:\\ProgramData"];
+#[cfg(windows)]
+const DENY_EXACT: &[&str] = &["C:\\", "C:\\Users"];
+#[cfg(windows)]
+const HOMES: &[&str] = &["C:\\Users"];
+
+///protection from tasks pointed to system directories or outside of the allowed roots
+#[derive(Clone, Debug, Default)]
+pub struct Guard
+{
+    allowed_roots: Vec<PathBuf>,
+    ///tasks without allowed roots are refused
+    restricted: bool,
+    ///tasks with `before`/`after` commands are refused
+    no_hooks: bool
+}
+
+impl Guard
+{
+    pub fn new(allowed_roots: Vec<PathBuf>, restricted: bool) -> Self
+    {
+        Self
+        {
+            allowed_roots: allowed_roots.iter().map(|r| normalize(r)).collect(),
+            restricted,
+            no_hooks: false
+        }
+    }
+    ///refuse tasks with commands, used for configs from usb
+    pub fn without_hooks(mut self) -> Self
+    {
+        self.no_hooks = true;
+        self
+    }
+    ///check that the task is allowed to run commands
+    pub fn check_hooks(&self, has_hooks: bool) -> Result<(), String>
+    {
+        if has_hooks && self.no_hooks
+        {
+            Err("Команды `before`/`after` в задачах с накопителя разрешены только с `usb_unrestricted = true` в локальном файле конфигурации, задача отклонена".to_owned())
+        }
+        else
+        {
+            Ok(())
+        }
+    }
+
+    ///check the task path, returns the reason of refusal
+    pub fn check(&self, path: &Path) -> Result<(), String>
+    {
+        let str_path = path.to_string_lossy();
+        let normalized = normalize(path);
+        if normalized.parent().is_none()
+        {
+            return Err(["Путь `", &str_path, "` является корнем файловой системы, задача отклонена"].concat());
+        }
+        let home = std::env::var_os("HOME").or(std::env::var_os("USERPROFILE")).map(|h| normalize(Path::new(&h)));
+        let exact = DENY_EXACT.iter().map(|d| normalize(Path::new(d))).chain(home);
+        let mut trees = DENY_TREES.iter().map(|d| normalize(Path::new(d)));
+        let mut homes = HOMES.iter().map(|d| normalize(Path::new(d)));
+        if exact.into_iter().any(|d| d == normalized) 
+        || trees.any(|d| normalized.starts_with(d))
+        || homes.any(|d| normalized.parent() == Some(d.as_path()))
+        {
+            return Err(["Путь `", &str_path, "` является защищенной системной директорией, задача отклонена"].concat());
+        }
+        if self.allowed_roots.is_empty()
+        {
+            return if self.restricted
+            {
+                Err(["Путь `", &str_path, "` отклонен, для задач с накопителя необходимо указать `allowed_roots` в локальном файле конфигурации"].concat())
+            }
+            else
+            {
+                Ok(())
+            };
+        }
+        if self.allowed_roots.iter().any(|r| normalized.starts_with(r))
+        {
+            Ok(())
+        }
+        else
+        {
+            Err(["Путь `", &str_path, "` находится вне разрешенных директорий `allowed_roots`, задача отклонена"].concat())
+        }
+    }
+}
+
+///absolute path with resolved links and `..`, case insensitive on windows
+fn normalize(path: &Path) -> PathBuf
+{
+    let path = std::fs::canonicalize(path)
+        .or_else(|_| std::path::absolute(path))
+        .unwrap_or(path.to_path_buf());
+    #[cfg(windows)]
+    let path = PathBuf::from(path.to_string_lossy().trim_start_matches(r"\\?\").to_lowercase());
+    path
+}
@@ -1,11 +1,22 @@
 use std::{pin::Pin, task::{Context, Poll}};
 use futures::Stream;
 use tokio::sync::mpsc::Receiver;
+use chrono::{Local, TimeZone};
 use utilites::Date;
 
+///seconds between dates in the local time of this computer, DST transitions are taken into account
 pub fn time_diff(current_date: &Date, checked_date: &Date) -> i64
 {
-    checked_date.as_naive_datetime().and_utc().timestamp() - current_date.as_naive_datetime().and_utc().timestamp()
+    timestamp(checked_date) - timestamp(current_date)
+}
+
+fn timestamp(date: &Date) -> i64
+{
+    let naive = date.as_naive_datetime();
+    Local.from_local_datetime(&naive)
+        .earliest()
+        .map(|d| d.timestamp())
+        .unwrap_or(naive.and_utc().timestamp())
 }
 
 #[derive(Debug)]
@@ -77,4 +88,198 @@ impl<T> From<Receiver<T>> for ReceiverStream<T>
     {
         Self::new(recv)
     }
-}
\ No newline at end of file
+}
+///parse human readable duration like `45s`, `1h30m`, `2d`, `1w`
+pub fn parse_duration(s: &str) -> Option<std::time::Duration>
+{
+    let s = s.trim();
+    if s.is_empty()
+    {
+        return None;
+    }
+    let mut seconds: u64 = 0;
+    let mut number = String::new();
+    for c in s.chars()
+    {
+        if c.is_ascii_digit()
+        {
+            number.push(c);
+        }
+        else if c.is_whitespace()
+        {
+            continue;
+        }
+        else
+        {
+            let n: u64 = number.parse().ok()?;
+            number.clear();
+            let multiplier = match c
+            {
+                's' => 1,
+                'm' => 60,
+                'h' => 60 * 60,
+                'd' => 24 * 60 * 60,
+                'w' => 7 * 24 * 60 * 60,
+                _ => return None
+            };
+            seconds = seconds.checked_add(n.checked_mul(multiplier)?)?;
+        }
+    }
+    //number without unit at the end
+    if !number.is_empty()
+    {
+        return None;
+    }
+    Some(std::time::Duration::from_secs(seconds))
+}
+
+///format duration to the string accepted by `parse_duration`
+pub fn format_duration(duration: &std::time::Duration) -> String
+{
+    let mut seconds = duration.as_secs();
+    if seconds == 0
+    {
+        return "0s".to_owned();
+    }
+    let mut result = String::new();
+    for (unit, size) in [("d", 24 * 60 * 60), ("h", 60 * 60), ("m", 60), ("s", 1)]
+    {
+        if seconds >= size
+        {
+            result.push_str(&(seconds / size).to_string());
+            result.push_str(unit);
+            seconds %= size;
+        }
+    }
+    result
+}
+
+///parse size like `20GiB`, `500MB`, `1024`, binary units for `K`, `M`, `G`, `T` without `B`
+pub fn parse_size(s: &str) -> Option<u64>
+{
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str()
+    {
+        "" | "b" => 1,
+        "kb" => 1000,
+        "mb" => 1000_u64.pow(2),
+        "gb" => 1000_u64.pow(3),
+        "tb" => 1000_u64.pow(4),
+        "k" | "kib" => 1024,
+        "m" | "mib" => 1024_u64.pow(2),
+        "g" | "gib" => 1024_u64.pow(3),
+        "t" | "tib" => 1024_u64.pow(4),
+        _ => return None
+    };
+    Some((number * multiplier as f64) as u64)
+}
+
+///format size to the string accepted by `parse_size`
+pub fn format_size(size: u64) -> String
+{
+    for (unit, multiplier) in [("TiB", 1024_u64.pow(4)), ("GiB", 1024_u64.pow(3)), ("MiB", 1024_u64.pow(2)), ("KiB", 1024)]
+    {
+        if size >= multiplier && size % multiplier == 0
+        {
+            return [(size / multiplier).to_string().as_str(), unit].concat();
+        }
+    }
+    size.to_string()
+}
+
+///serde for durations written as `45s`, `1h30m`
+pub mod duration
+{
+    use std::time::Duration;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use super::{format_duration, parse_duration};
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        parse_duration(&s).ok_or_else(|| serde::de::Error::custom(["Ошибка формата длительности ", &s].concat()))
+    }
+
+    pub fn serialize<S>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&format_duration(duration))
+    }
+}
+
+///serde for optional fields with `duration`
+pub mod duration_option
+{
+    use std::time::Duration;
+    use serde::{Deserializer, Serializer};
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        super::duration::deserialize(deserializer).map(Some)
+    }
+
+    pub fn serialize<S>(duration: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match duration
+        {
+            Some(d) => super::duration::serialize(d, serializer),
+            None => serializer.serialize_none()
+        }
+    }
+}
+
+///serde for sizes written as `20GiB`, `500MB`
+pub mod size
+{
+    use serde::{Deserialize, Deserializer, Serializer};
+    use super::{format_size, parse_size};
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<u64, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        parse_size(&s).ok_or_else(|| serde::de::Error::custom(["Ошибка формата размера ", &s].concat()))
+    }
+
+    pub fn serialize<S>(size: &u64, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&format_size(*size))
+    }
+}
+
+///serde for optional fields with `size`
+pub mod size_option
+{
+    use serde::{Deserializer, Serializer};
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        super::size::deserialize(deserializer).map(Some)
+    }
+
+    pub fn serialize<S>(size: &Option<u64>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match size
+        {
+            Some(s) => super::size::serialize(s, serializer),
+            None => serializer.serialize_none()
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use std::{collections::BTreeMap, path::{Path, PathBuf}, process::Stdio, time::Duration};
+use serde::{Deserialize, Serialize};
+use crate::helpers::format_duration;
+
+///command executed before or after the task processing
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct Hook
+{
+    ///program and its arguments
+    pub command: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub working_dir: Option<PathBuf>,
+    #[serde(default = "default_timeout")]
+    #[serde(with = "crate::helpers::duration")]
+    pub timeout: Duration,
+    ///additional environment variables
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>
+}
+
+fn default_timeout() -> Duration
+{
+    Duration::from_secs(60)
+}
+
+impl Hook
+{
+    ///run the command with `DELTIME_PATH`, `DELTIME_TASK_ID` and `DELTIME_STATUS` (only after processing) variables
+    pub async fn run(&self, path: &Path, task_id: &str, status: Option<&str>) -> Result<(), String>
+    {
+        let Some((program, args)) = self.command.split_first() else
+        {
+            return Err("команда не указана".to_owned());
+        };
+        let mut cmd = tokio::process::Command::new(program);
+        cmd.args(args)
+            .envs(&self.env)
+            .env("DELTIME_PATH", path)
+            .env("DELTIME_TASK_ID", task_id)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        if let Some(status) = status
+        {
+            cmd.env("DELTIME_STATUS", status);
+        }
+        if let Some(dir) = self.working_dir.as_ref()
+        {
+            cmd.current_dir(dir);
+        }
+        let output = match tokio::time::timeout(self.timeout, cmd.output()).await
+        {
+            Ok(output) => output.map_err(|e| ["ошибка запуска `", program, "`: ", &e.to_string()].concat())?,
+            Err(_) => return Err(["превышено время ожидания `", program, "` ", &format_duration(&self.timeout)].concat())
+        };
+        if output.status.success()
+        {
+            Ok(())
+        }
+        else
+        {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            Err(["`", program, "` завершилась с ошибкой ", &output.status.to_string(), ": ", stderr.trim()].concat())
+        }
+    }
+    pub fn command_line(&self) -> String
+    {
+        self.command.join(" ")
+    }
+}
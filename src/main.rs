@@ -6,6 +6,23 @@ mod usb;
 mod error;
 mod tasker;
 mod helpers;
+mod targets;
+mod shred;
+mod trash;
+mod archive;
+mod filters;
+mod guard;
+mod retry;
+mod verify;
+mod openfiles;
+mod hooks;
+mod truncate;
+mod rotate;
+mod audit;
+mod freespace;
+mod cron;
+mod repeat;
+mod zone;
 #[cfg(feature = "window")]
 mod window;
 mod config;
@@ -14,11 +31,12 @@ mod beeper;
 use std::{collections::HashMap, path::{Path, PathBuf}, sync::Arc};
 use futures::StreamExt;
 use indicatif::MultiProgress;
-use progressbars::{progress_bar_for_datetime, progress_bar_for_interval};
+use progressbars::{progress_bar_for_datetime, progress_bar_for_free_space, progress_bar_for_interval};
 use scheduler::Scheduler;
 use structs::TaskWithProgress;
 use config::Config;
 use tasker::Handler;
+use guard::Guard;
 use tokio::sync::RwLock;
 use usb::usb_event;
 
@@ -28,6 +46,16 @@ use usb::usb_event;
 async fn main() 
 {
     let _ = logger::StructLogger::new_default();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().is_some_and(|a| a == "audit")
+    {
+        let path = Config::load_from_path(config::FILE_NAME).ok()
+            .and_then(|c| c.audit)
+            .unwrap_or_default()
+            .path;
+        audit::print(&path, &args[1..]).await;
+        return;
+    }
     let config =  Config::load().await;
     run_process(config).await;
 }
@@ -37,8 +65,11 @@ async fn run_process(cfg: Config)
     let mpb = MultiProgress::default();
     let tasks: Arc<RwLock<HashMap<Arc<String>, TaskWithProgress>>> = Arc::new(RwLock::new(HashMap::new()));
     let scheduler: Scheduler<Arc<String>> = Scheduler::new();
-    cfg.add_tasks(mpb.clone(), tasks.clone(), scheduler.clone()).await;
-    usb_checker(mpb.clone(), tasks.clone(), scheduler.clone());
+    let guard = cfg.guard();
+    let usb_guard = cfg.usb_guard();
+    audit::init(cfg.audit.clone());
+    cfg.add_tasks(mpb.clone(), tasks.clone(), scheduler.clone(), &guard).await;
+    usb_checker(mpb.clone(), tasks.clone(), scheduler.clone(), usb_guard);
     let handler = Handler::new(tasks);
     //hide process when start
     #[cfg(all(target_os = "linux", feature = "window"))]
@@ -47,7 +78,7 @@ async fn run_process(cfg: Config)
 }
 
 #[cfg(all(target_os = "linux", feature = "usb"))]
-fn usb_checker(mpb: MultiProgress, tasks:  Arc<RwLock<HashMap<Arc<String>, TaskWithProgress>>>, scheduler: Scheduler<Arc<String>>)
+fn usb_checker(mpb: MultiProgress, tasks:  Arc<RwLock<HashMap<Arc<String>, TaskWithProgress>>>, scheduler: Scheduler<Arc<String>>, guard: Guard)
 {   
     tokio::spawn(async move 
     {
@@ -55,7 +86,7 @@ fn usb_checker(mpb: MultiProgress, tasks:  Arc<RwLock<HashMap<Arc<String>, TaskW
         {
             while let Some(path) = stream.next().await
             {
-                usb_path_worker(mpb.clone(), tasks.clone(), scheduler.clone(), path).await;
+                usb_path_worker(mpb.clone(), tasks.clone(), scheduler.clone(), path, &guard).await;
             }
         }
     });
@@ -63,7 +94,7 @@ fn usb_checker(mpb: MultiProgress, tasks:  Arc<RwLock<HashMap<Arc<String>, TaskW
 
 ///correctly working if wrapping into futures executor
 #[cfg(all(target_os = "windows", feature = "usb"))]
-fn usb_checker(mpb: MultiProgress, tasks:  Arc<RwLock<HashMap<Arc<String>, TaskWithProgress>>>, scheduler: Scheduler<Arc<String>>)
+fn usb_checker(mpb: MultiProgress, tasks:  Arc<RwLock<HashMap<Arc<String>, TaskWithProgress>>>, scheduler: Scheduler<Arc<String>>, guard: Guard)
 {   
     tokio::task::spawn_blocking(move ||
     {
@@ -73,7 +104,7 @@ fn usb_checker(mpb: MultiProgress, tasks:  Arc<RwLock<HashMap<Arc<String>, TaskW
             {
                 while let Some(path) = stream.next().await
                 {
-                    usb_path_worker(mpb.clone(), tasks.clone(), scheduler.clone(), path).await;
+                    usb_path_worker(mpb.clone(), tasks.clone(), scheduler.clone(), path, &guard).await;
                 }
             }
         });
@@ -81,7 +112,7 @@ fn usb_checker(mpb: MultiProgress, tasks:  Arc<RwLock<HashMap<Arc<String>, TaskW
     
 }
 
-async fn usb_path_worker(mpb: MultiProgress, tasks:  Arc<RwLock<HashMap<Arc<String>, TaskWithProgress>>>, scheduler: Scheduler<Arc<String>>, path: PathBuf)
+async fn usb_path_worker(mpb: MultiProgress, tasks:  Arc<RwLock<HashMap<Arc<String>, TaskWithProgress>>>, scheduler: Scheduler<Arc<String>>, path: PathBuf, guard: &Guard)
 {
     let path = Path::new(&path).join(config::FILE_NAME);
     //logger::debug!("usb path: {}", path.display());
@@ -89,7 +120,7 @@ async fn usb_path_worker(mpb: MultiProgress, tasks:  Arc<RwLock<HashMap<Arc<Stri
     if let Ok(cfg) = config
     {
         let _ = mpb.println(format!("Файл конфигурации успешно загружен с найденого накопителя {}", path.display()));
-        cfg.add_tasks(mpb, tasks, scheduler).await
+        cfg.add_tasks(mpb, tasks, scheduler, guard).await
     }
     else 
     {
@@ -101,10 +132,9 @@ async fn usb_path_worker(mpb: MultiProgress, tasks:  Arc<RwLock<HashMap<Arc<Stri
 #[cfg(test)]
 mod tests
 {
-    use std::path::PathBuf;
-    use scheduler::RepeatingStrategy;
+    use std::{path::PathBuf, time::Duration};
     use utilites::Date;
-    use crate::{helpers::time_diff, structs::Task, config::{FILE_NAME, Config}};
+    use crate::{archive::{Archive, ArchiveFormat}, audit::{AuditEntry, AuditFilter, Outcome}, cron::Cron, filters::{self, Globs, KeepBy}, repeat::{Calendar, Repeat}, zone::ZonedDate, freespace::{Threshold, Trigger, Usage}, guard::Guard, helpers::{format_duration, format_size, parse_duration, parse_size, time_diff}, structs::Task, targets::{self, Target}, config::{FILE_NAME, Config}};
 
     #[test]
     fn test_deserialize()
@@ -124,6 +154,406 @@ mod tests
         logger::info!("{:?}", diff);
     }
 
+    #[test]
+    fn test_parse_duration()
+    {
+        assert_eq!(parse_duration("45s"), Some(std::time::Duration::from_secs(45)));
+        assert_eq!(parse_duration("1h30m"), Some(std::time::Duration::from_secs(90 * 60)));
+        assert_eq!(parse_duration("2d"), Some(std::time::Duration::from_secs(2 * 24 * 60 * 60)));
+        assert_eq!(parse_duration("1h 30"), None);
+        assert_eq!(parse_duration("5y"), None);
+        assert_eq!(format_duration(&parse_duration("1d1h30m").unwrap()), "1d1h30m");
+    }
+
+    #[test]
+    fn test_parse_size()
+    {
+        assert_eq!(parse_size("20GiB"), Some(20 * 1024 * 1024 * 1024));
+        assert_eq!(parse_size("500MB"), Some(500_000_000));
+        assert_eq!(parse_size("1.5K"), Some(1536));
+        assert_eq!(parse_size("1024"), Some(1024));
+        assert_eq!(parse_size("10 parrots"), None);
+        assert_eq!(format_size(20 * 1024 * 1024 * 1024), "20GiB");
+    }
+
+    #[test]
+    fn test_audit_filter()
+    {
+        let entry = AuditEntry
+        {
+            time: "2025-02-11T02:30:00".to_owned(),
+            task: "abc".to_owned(),
+            path: PathBuf::from("/var/tmp/exports/a.log"),
+            size: 10,
+            mtime: None,
+            blake3: None,
+            mode: "delete".to_owned(),
+            outcome: Outcome::Ok,
+            reason: None
+        };
+        let args = |a: &[&str]| a.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let filter = AuditFilter::from_args(&args(&["--task", "abc", "--path", "/var/tmp", "--from", "2025-02-11", "--to", "2025-02-12"])).unwrap();
+        assert!(filter.matches(&entry));
+        assert!(!AuditFilter::from_args(&args(&["--task", "other"])).unwrap().matches(&entry));
+        assert!(!AuditFilter::from_args(&args(&["--path", "/var/tmp/exp"])).unwrap().matches(&entry));
+        assert!(!AuditFilter::from_args(&args(&["--from", "2025-02-11T03:00:00"])).unwrap().matches(&entry));
+        //date without time in `--to` includes the whole day
+        assert!(AuditFilter::from_args(&args(&["--from", "2025-02-11", "--to", "2025-02-11"])).unwrap().matches(&entry));
+        assert!(!AuditFilter::from_args(&args(&["--to", "2025-02-10"])).unwrap().matches(&entry));
+        assert!(!AuditFilter::from_args(&args(&["--to", "2025-02-11T02:00:00"])).unwrap().matches(&entry));
+        assert!(AuditFilter::from_args(&args(&["--unknown", "x"])).is_err());
+    }
+
+    #[test]
+    fn test_cron()
+    {
+        let next = |expr: &str, after: &str|
+        {
+            let after = Date::parse(after).unwrap().as_naive_datetime();
+            Cron::parse(expr).unwrap().next_after(&after).map(|n| n.format("%Y-%m-%dT%H:%M:%S").to_string())
+        };
+        //friday after 02:30 -> monday
+        assert_eq!(next("30 2 * * 1-5", "2025-02-14T03:00:00").as_deref(), Some("2025-02-17T02:30:00"));
+        assert_eq!(next("*/15 8-17 * * *", "2025-02-14T17:50:00").as_deref(), Some("2025-02-15T08:00:00"));
+        assert_eq!(next("*/15 8-17 * * *", "2025-02-14T09:00:00").as_deref(), Some("2025-02-14T09:15:00"));
+        assert_eq!(next("0 0 29 feb *", "2025-01-01T00:00:00").as_deref(), Some("2028-02-29T00:00:00"));
+        assert_eq!(next("0 0 30 2 *", "2025-01-01T00:00:00"), None);
+        assert!(Cron::parse("61 * * * *").is_err());
+        assert!(Cron::parse("* * *").is_err());
+        assert!(Cron::parse("5-1 * * * *").is_err());
+    }
+
+    #[test]
+    fn test_interval()
+    {
+        let interval = |value: &str|
+        {
+            let json = ["{\"path\": \"/tmp\", \"repeat\": \"forever\", \"interval\": ", value, "}"].concat();
+            serde_json::from_str::<Task>(&json).map(|t| t.interval)
+        };
+        assert_eq!(interval("5").unwrap(), Some(Duration::from_secs(5 * 60)));
+        assert_eq!(interval("\"45s\"").unwrap(), Some(Duration::from_secs(45)));
+        assert_eq!(interval("\"1h30m\"").unwrap(), Some(Duration::from_secs(90 * 60)));
+        assert_eq!(interval("\"2d\"").unwrap(), Some(Duration::from_secs(2 * 24 * 60 * 60)));
+        assert!(interval("\"0s\"").is_err());
+        assert!(interval("\"soon\"").is_err());
+        let task = Task { interval: Some(Duration::from_secs(90)), ..Default::default() };
+        assert!(serde_json::to_string(&task).unwrap().contains("\"interval\":\"1m30s\""));
+    }
+
+    #[test]
+    fn test_calendar()
+    {
+        let next = |calendar: Calendar, start: &str, now: &str|
+        {
+            let start = Date::parse(start).unwrap().as_naive_datetime();
+            let now = Date::parse(now).unwrap().as_naive_datetime();
+            calendar.next_after(&start, &now).map(|n| n.format("%Y-%m-%dT%H:%M:%S").to_string())
+        };
+        let weekly = Calendar::new(Repeat::Weekly, &[], None, None).unwrap();
+        assert_eq!(next(weekly, "2025-02-14T02:30:00", "2025-02-20T10:00:00").as_deref(), Some("2025-02-21T02:30:00"));
+        let weekdays = Calendar::new(Repeat::Forever, &["mon".to_owned(), "thu".to_owned()], None, None).unwrap();
+        //friday -> monday
+        assert_eq!(next(weekdays, "2025-02-14T02:30:00", "2025-02-14T10:00:00").as_deref(), Some("2025-02-17T02:30:00"));
+        assert_eq!(next(weekdays, "2025-02-14T02:30:00", "2025-02-17T02:00:00").as_deref(), Some("2025-02-17T02:30:00"));
+        let yearly = Calendar::new(Repeat::Yearly, &[], None, None).unwrap();
+        assert_eq!(next(yearly, "2024-02-29T00:00:00", "2024-03-01T00:00:00").as_deref(), Some("2025-02-28T00:00:00"));
+        //the first run is years ahead
+        assert_eq!(next(yearly, "2027-06-01T00:00:00", "2025-02-01T00:00:00").as_deref(), Some("2027-06-01T00:00:00"));
+        let every = Calendar::new(Repeat::Forever, &[], Some(Duration::from_secs(3 * 24 * 60 * 60)), None).unwrap();
+        assert_eq!(next(every, "2025-02-01T12:00:00", "2025-02-05T00:00:00").as_deref(), Some("2025-02-07T12:00:00"));
+        assert!(Calendar::new(Repeat::Dialy, &[], None, None).is_none());
+        assert_eq!(Repeat::parse("daily"), Some(Repeat::Dialy));
+    }
+
+    #[test]
+    fn test_timezone()
+    {
+        let moscow: chrono_tz::Tz = "Europe/Moscow".parse().unwrap();
+        let berlin: chrono_tz::Tz = "Europe/Berlin".parse().unwrap();
+        let local = |date: &str, zone| ZonedDate::parse(date).unwrap().to_local(zone).unwrap().as_naive_datetime();
+        assert_eq!(local("2025-02-15T21:33:44+03:00", None), local("2025-02-15T21:33:44", Some(moscow)));
+        assert_eq!(local("2025-02-15T18:33:44Z", Some(berlin)), local("2025-02-15T21:33:44", Some(moscow)));
+        //daily cycle keeps 03:00 in Berlin across the transition to summer time
+        let daily = Calendar::new(Repeat::Dialy, &[], None, Some(berlin)).unwrap();
+        let start = local("2025-03-29T03:00:00", Some(berlin));
+        let now = start + chrono::TimeDelta::minutes(1);
+        assert_eq!(daily.next_after(&start, &now), Some(local("2025-03-30T03:00:00", Some(berlin))));
+        let monthly = Calendar::new(Repeat::Monthly, &[], None, Some(berlin)).unwrap();
+        let start = local("2025-01-31T03:00:00", Some(berlin));
+        let now = start + chrono::TimeDelta::minutes(1);
+        assert_eq!(monthly.next_after(&start, &now), Some(local("2025-02-28T03:00:00", Some(berlin))));
+        assert!(Calendar::new(Repeat::Once, &[], None, Some(berlin)).is_none());
+    }
+
+    #[test]
+    fn test_free_space_trigger()
+    {
+        assert_eq!(Threshold::parse("5GiB"), Some(Threshold::Bytes(5 * 1024 * 1024 * 1024)));
+        assert_eq!(Threshold::parse("10%"), Some(Threshold::Percent(10)));
+        assert_eq!(Threshold::parse("150%"), None);
+        let mut trigger = Trigger::new(Threshold::Percent(10));
+        let usage = |free| Usage { free, total: 1000 };
+        assert!(!trigger.check(&usage(500)));
+        assert!(trigger.check(&usage(90)));
+        //no repeated firing until free space rises above the threshold plus hysteresis
+        assert!(!trigger.check(&usage(80)));
+        assert!(!trigger.check(&usage(105)));
+        assert!(!trigger.check(&usage(95)));
+        assert!(!trigger.check(&usage(120)));
+        assert!(trigger.check(&usage(50)));
+    }
+
+    ///empty directory inside of the system temp directory
+    fn temp_dir(name: &str) -> PathBuf
+    {
+        let dir = std::env::temp_dir().join(["deltime_test_", name].concat());
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_trash_names()
+    {
+        use crate::trash::{encode_path, reserve_name};
+        assert_eq!(encode_path(std::path::Path::new("/tmp/my file%.log")), "/tmp/my%20file%25.log");
+        assert_eq!(encode_path(std::path::Path::new("/tmp/отчет")), "/tmp/%D0%BE%D1%82%D1%87%D0%B5%D1%82");
+        let dir = temp_dir("trash");
+        let (files, info) = (dir.join("files"), dir.join("info"));
+        std::fs::create_dir_all(&files).unwrap();
+        std::fs::create_dir_all(&info).unwrap();
+        let name = std::ffi::OsStr::new("a.log");
+        let original = std::path::Path::new("/var/tmp/a b.log");
+        let (info_file, trashed) = reserve_name(&files, &info, name, original).unwrap();
+        assert_eq!(trashed, files.join("a.log"));
+        let content = std::fs::read_to_string(&info_file).unwrap();
+        assert!(content.starts_with("[Trash Info]\nPath=/var/tmp/a%20b.log\nDeletionDate="));
+        //name is taken by the info file of the previous call and by the file without info
+        std::fs::write(files.join("a.3.log"), "").unwrap();
+        assert_eq!(reserve_name(&files, &info, name, original).unwrap().1, files.join("a.2.log"));
+        assert_eq!(reserve_name(&files, &info, name, original).unwrap().1, files.join("a.4.log"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_archive_destination()
+    {
+        let archive = |destination: &str| Archive { destination: PathBuf::from(destination), name: "{name}".to_owned(), format: ArchiveFormat::TarZst };
+        assert!(archive("/var/exports/arch").check_destination(std::path::Path::new("/var/exports")).is_err());
+        assert!(archive("/var/exports").check_destination(std::path::Path::new("/var/exports")).is_err());
+        assert!(archive("/var/exports_arch").check_destination(std::path::Path::new("/var/exports")).is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_follow_link()
+    {
+        let dir = temp_dir("follow");
+        std::fs::write(dir.join("real.log"), "12345").unwrap();
+        std::fs::create_dir(dir.join("real")).unwrap();
+        std::os::unix::fs::symlink(dir.join("real.log"), dir.join("link.log")).unwrap();
+        std::os::unix::fs::symlink(dir.join("real"), dir.join("link")).unwrap();
+        std::os::unix::fs::symlink(dir.join("missing"), dir.join("broken")).unwrap();
+        let file = filters::follow_link(&dir.join("link.log")).unwrap();
+        assert_eq!(file.path, dir.join("real.log").canonicalize().unwrap());
+        assert!(!file.is_link && !file.is_dir && file.size == 5);
+        let linked_dir = filters::follow_link(&dir.join("link")).unwrap();
+        assert_eq!(linked_dir.path, dir.join("real").canonicalize().unwrap());
+        assert!(linked_dir.is_dir && !linked_dir.is_link);
+        assert!(filters::follow_link(&dir.join("broken")).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_truncate()
+    {
+        use crate::truncate::{lines_offset, truncate, CHUNK_SIZE};
+        let dir = temp_dir("truncate");
+        let file = dir.join("app.log");
+        let truncated = |content: &str, keep_bytes: Option<u64>, keep_lines: Option<usize>|
+        {
+            std::fs::write(&file, content).unwrap();
+            let freed = truncate(&file, keep_bytes, keep_lines).unwrap();
+            (freed, std::fs::read_to_string(&file).unwrap())
+        };
+        assert_eq!(truncated("a\nb\nc\n", None, Some(2)), (2, "b\nc\n".to_owned()));
+        assert_eq!(truncated("a\nb\nc", None, Some(2)), (2, "b\nc".to_owned()));
+        assert_eq!(truncated("a\nb\nc\n", None, Some(5)), (0, "a\nb\nc\n".to_owned()));
+        assert_eq!(truncated("a\nb\nc\n", None, Some(0)), (6, "".to_owned()));
+        assert_eq!(truncated("a\nb\nc\n", Some(3), None), (3, "\nc\n".to_owned()));
+        //the larger tail of both limits is kept
+        assert_eq!(truncated("a\nb\nc\n", Some(1), Some(2)), (2, "b\nc\n".to_owned()));
+        //newlines around the boundary of the chunks read from the end
+        for pad in CHUNK_SIZE - 3..CHUNK_SIZE + 3
+        {
+            let content = ["first\n", &"y".repeat(pad as usize), "\ntail\n"].concat();
+            std::fs::write(&file, &content).unwrap();
+            let mut f = std::fs::File::open(&file).unwrap();
+            let len = content.len() as u64;
+            assert_eq!(lines_offset(&mut f, len, 1).unwrap(), len - 5);
+            assert_eq!(lines_offset(&mut f, len, 2).unwrap(), 6);
+            assert_eq!(lines_offset(&mut f, len, 3).unwrap(), 0);
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_rotate()
+    {
+        use crate::rotate::{is_generation, parse_generation, rotate, Compression, Rotate};
+        assert_eq!(parse_generation("app.log.3.gz"), Some(("app.log", 3)));
+        assert_eq!(parse_generation("app.log"), None);
+        let dir = temp_dir("rotate");
+        let file = dir.join("app.log");
+        let settings = Rotate { keep: 2, compress: None, create: true };
+        for content in ["first", "second", "third"]
+        {
+            std::fs::write(&file, content).unwrap();
+            assert_eq!(rotate(&file, &settings).unwrap(), dir.join("app.log.1"));
+        }
+        //the oldest generation is removed when `keep` is reached
+        assert_eq!(std::fs::read_to_string(dir.join("app.log.1")).unwrap(), "third");
+        assert_eq!(std::fs::read_to_string(dir.join("app.log.2")).unwrap(), "second");
+        assert!(!dir.join("app.log.3").exists());
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "");
+        let settings = Rotate { keep: 3, compress: Some(Compression::Gzip), create: false };
+        assert_eq!(rotate(&file, &settings).unwrap(), dir.join("app.log.1.gz"));
+        assert!(dir.join("app.log.2").exists() && dir.join("app.log.3").exists() && !file.exists());
+        //generations exist only next to the base file
+        std::fs::write(dir.join("dump.2025"), "").unwrap();
+        assert!(!is_generation(&dir.join("dump.2025")));
+        std::fs::write(dir.join("dump"), "").unwrap();
+        assert!(is_generation(&dir.join("dump.2025")));
+        let task: Task = serde_json::from_str("{\"path\": \"/tmp\", \"repeat\": \"once\", \"rotate\": 7}").unwrap();
+        assert_eq!(task.rotate.map(|r| r.keep), Some(7));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    ///file of `size` bytes modified `age` seconds ago
+    fn aged_file(dir: &std::path::Path, name: &str, size: usize, age: u64) -> Target
+    {
+        let path = dir.join(name);
+        std::fs::write(&path, vec![0u8; size]).unwrap();
+        let mtime = std::time::SystemTime::now() - Duration::from_secs(age);
+        std::fs::File::options().write(true).open(&path).unwrap().set_modified(mtime).unwrap();
+        Target::file(path, size as u64)
+    }
+
+    fn names(targets: &[Target]) -> Vec<String>
+    {
+        targets.iter().map(|t| t.path.file_name().unwrap().to_string_lossy().into_owned()).collect()
+    }
+
+    #[test]
+    fn test_over_quota()
+    {
+        let dir = temp_dir("quota");
+        let old = aged_file(&dir, "old.log", 100, 300);
+        let middle = aged_file(&dir, "middle.log", 200, 200);
+        let new = aged_file(&dir, "new.log", 300, 100);
+        let usage = vec![new.clone(), old.clone(), middle.clone()];
+        let all = vec![middle.clone(), new.clone(), old.clone()];
+        //the oldest files are removed first until the directory fits
+        assert_eq!(names(&filters::over_quota(&usage, all.clone(), Some(350), None)), ["old.log", "middle.log"]);
+        assert_eq!(names(&filters::over_quota(&usage, all.clone(), Some(600), None)), Vec::<String>::new());
+        assert_eq!(names(&filters::over_quota(&usage, all.clone(), None, Some(2))), ["old.log"]);
+        assert_eq!(names(&filters::over_quota(&usage, all.clone(), Some(500), Some(1))), ["old.log", "middle.log"]);
+        //files outside of the selection are counted, but never removed
+        assert_eq!(names(&filters::over_quota(&usage, vec![new.clone(), middle.clone()], Some(350), None)), ["middle.log", "new.log"]);
+        assert_eq!(names(&filters::over_quota(&usage, all, None, None)), Vec::<String>::new());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_keep_last()
+    {
+        let dir = temp_dir("keep_last");
+        //order of names differs from order of modification
+        let files = vec![aged_file(&dir, "backup_1", 1, 100), aged_file(&dir, "backup_2", 1, 300), aged_file(&dir, "backup_3", 1, 200)];
+        assert_eq!(names(&filters::keep_last(files.clone(), 1, KeepBy::Mtime)), ["backup_2", "backup_3"]);
+        assert_eq!(names(&filters::keep_last(files.clone(), 2, KeepBy::Mtime)), ["backup_2"]);
+        assert_eq!(names(&filters::keep_last(files.clone(), 1, KeepBy::Name)), ["backup_1", "backup_2"]);
+        assert_eq!(names(&filters::keep_last(files.clone(), 0, KeepBy::Name)), ["backup_1", "backup_2", "backup_3"]);
+        assert_eq!(names(&filters::keep_last(files, 5, KeepBy::Mtime)), Vec::<String>::new());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_prune_empty_dirs()
+    {
+        let dir = temp_dir("prune");
+        std::fs::create_dir_all(dir.join("a/b")).unwrap();
+        std::fs::create_dir_all(dir.join("c")).unwrap();
+        std::fs::write(dir.join("c/file"), "").unwrap();
+        std::fs::create_dir_all(dir.join("d/keep")).unwrap();
+        std::fs::create_dir_all(dir.join("e")).unwrap();
+        std::fs::write(dir.join("e/removed.log"), "").unwrap();
+        let excluded = |p: &std::path::Path| p.ends_with("keep");
+        let pruned = |mut dirs: Vec<PathBuf>| { dirs.sort(); dirs };
+        //files from `removed` don't keep the directory in dry run
+        let removed: std::collections::HashSet<PathBuf> = [dir.join("e/removed.log")].into();
+        let expected = vec![dir.join("a"), dir.join("a/b"), dir.join("e")];
+        assert_eq!(pruned(targets::prune_empty_dirs(&dir, true, &removed, true, &excluded)), expected);
+        assert!(dir.join("a/b").exists());
+        std::fs::remove_file(dir.join("e/removed.log")).unwrap();
+        assert_eq!(pruned(targets::prune_empty_dirs(&dir, true, &removed, false, &excluded)), expected);
+        assert!(!dir.join("a").exists() && !dir.join("e").exists() && dir.join("d/keep").exists());
+        //the root is removed only when it is left empty and `with_root` is set
+        std::fs::remove_file(dir.join("c/file")).unwrap();
+        assert_eq!(pruned(targets::prune_empty_dirs(&dir, false, &removed, false, &|_| false)), vec![dir.join("c"), dir.join("d"), dir.join("d/keep")]);
+        assert!(dir.exists());
+        assert_eq!(targets::prune_empty_dirs(&dir, true, &removed, false, &|_| false), vec![dir.clone()]);
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn test_globs()
+    {
+        let include = vec!["**/*.log".to_owned(), "**/*.tmp".to_owned()];
+        let exclude = vec!["**/keep/**".to_owned(), "important.log".to_owned()];
+        let globs = Globs::new(&include, &exclude, None).unwrap();
+        assert!(globs.is_match(std::path::Path::new("app.log")));
+        assert!(globs.is_match(std::path::Path::new("a/b/cache.tmp")));
+        assert!(!globs.is_match(std::path::Path::new("a/keep/app.log")));
+        assert!(!globs.is_match(std::path::Path::new("a/important.log")));
+        assert!(!globs.is_match(std::path::Path::new("a/readme.txt")));
+        let mask = "*.delme".to_owned();
+        let globs = Globs::new(&[], &exclude, Some(&mask)).unwrap();
+        assert!(globs.is_match(std::path::Path::new("x.delme")));
+        assert!(!globs.is_match(std::path::Path::new("sub/x.delme")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_guard()
+    {
+        let guard = Guard::new(Vec::new(), false);
+        assert!(guard.check(std::path::Path::new("/")).is_err());
+        assert!(guard.check(std::path::Path::new("/home")).is_err());
+        assert!(guard.check(std::path::Path::new("/usr/share/doc")).is_err());
+        assert!(guard.check(std::path::Path::new("/var/log")).is_err());
+        assert!(guard.check(std::path::Path::new("/var/log/app")).is_ok());
+        assert!(guard.check(std::path::Path::new("/home/alice")).is_err());
+        assert!(guard.check(std::path::Path::new("/home/alice/Downloads")).is_ok());
+        assert!(guard.check(std::path::Path::new("/var/tmp/exports")).is_ok());
+        let guard = Guard::new(vec!["/var/tmp".into()], true);
+        assert!(guard.check(std::path::Path::new("/var/tmp/exports")).is_ok());
+        assert!(guard.check(std::path::Path::new("/srv/data")).is_err());
+        assert!(Guard::new(Vec::new(), true).check(std::path::Path::new("/var/tmp/exports")).is_err());
+        //usb tasks outside of `allowed_roots` are accepted only with `usb_unrestricted`, protected paths never
+        let mut config = Config { allowed_roots: vec!["/var/tmp".into()], ..Default::default() };
+        assert!(config.usb_guard().check(std::path::Path::new("/srv/data")).is_err());
+        config.usb_unrestricted = true;
+        assert!(config.usb_guard().check(std::path::Path::new("/srv/data")).is_ok());
+        assert!(config.usb_guard().check(std::path::Path::new("/etc")).is_err());
+        assert!(config.usb_guard().check_hooks(true).is_ok());
+        assert!(Config::default().usb_guard().check_hooks(true).is_err());
+        assert!(Config::default().usb_guard().check_hooks(false).is_ok());
+        assert!(Config::default().guard().check_hooks(true).is_ok());
+    }
+
     #[test]
     fn test_serialize()
     {
@@ -151,66 +581,74 @@ mod tests
                 {
                     path: PathBuf::from(name("1")),
                     mask: None,
-                    interval: Some(1),
-                    date: None,
-                    repeat: RepeatingStrategy::Once,
-                    visible: true
+                    interval: Some(Duration::from_secs(60)),
+                    date_config: None,
+                    repeat: Repeat::Once,
+                    visible: true,
+                    ..Default::default()
                 },
                 Task
                 {
                     path: PathBuf::from(name("2")),
                     mask: None,
                     interval: None,
-                    date: Some(Date::now().add_minutes(3)),
-                    repeat: RepeatingStrategy::Once,
-                    visible: true
+                    date_config: Some(Date::now().add_minutes(3).into()),
+                    repeat: Repeat::Once,
+                    visible: true,
+                    ..Default::default()
                 },
                 Task
                 {
                     path: PathBuf::from(name("3")),
                     mask: None,
                     interval: None,
-                    date: Some(Date::now().add_minutes(6)),
-                    repeat: RepeatingStrategy::Once,
-                    visible: true
+                    date_config: Some(Date::now().add_minutes(6).into()),
+                    repeat: Repeat::Once,
+                    visible: true,
+                    ..Default::default()
                 },
                 Task
                 {
                     path: PathBuf::from(name("4")),
                     mask: None,
                     interval: None,
-                    date: Some(Date::now().add_minutes(3)),
-                    repeat: RepeatingStrategy::Dialy,
-                    visible: false
+                    date_config: Some(Date::now().add_minutes(3).into()),
+                    repeat: Repeat::Dialy,
+                    visible: false,
+                    ..Default::default()
                 },
                 Task
                 {
                     path: PathBuf::from(name("5")),
                     mask: Some("*.delme".into()),
-                    interval: Some(3),
-                    date: None,
-                    repeat: RepeatingStrategy::Forever,
-                    visible: true
+                    interval: Some(Duration::from_secs(3 * 60)),
+                    date_config: None,
+                    repeat: Repeat::Forever,
+                    visible: true,
+                    ..Default::default()
                 },
                 Task
                 {
                     path: PathBuf::from(name("not_exists")),
                     mask: None,
-                    interval: Some(1),
-                    date: None,
-                    repeat: RepeatingStrategy::Once,
-                    visible: true
+                    interval: Some(Duration::from_secs(60)),
+                    date_config: None,
+                    repeat: Repeat::Once,
+                    visible: true,
+                    ..Default::default()
                 },
                 Task
                 {
                     path: PathBuf::from(name("expired")),
                     mask: None,
                     interval: None,
-                    date: Some(Date::now().sub_minutes(3)),
-                    repeat: RepeatingStrategy::Once,
-                    visible: true
+                    date_config: Some(Date::now().sub_minutes(3).into()),
+                    repeat: Repeat::Once,
+                    visible: true,
+                    ..Default::default()
                 },
-            ]
+            ],
+            ..Default::default()
         };
         let _ = utilites::serialize(cfg, FILE_NAME, false, utilites::Serializer::Toml);
         //usb test
@@ -225,39 +663,44 @@ mod tests
                 {
                     path: PathBuf::from(name("usb_1")),
                     mask: None,
-                    interval: Some(2),
-                    date: None,
-                    repeat: RepeatingStrategy::Once,
-                    visible: true
+                    interval: Some(Duration::from_secs(2 * 60)),
+                    date_config: None,
+                    repeat: Repeat::Once,
+                    visible: true,
+                    ..Default::default()
                 },
                 Task
                 {
                     path: PathBuf::from(name("usb_2")),
                     mask: None,
                     interval: None,
-                    date: Some(Date::now().add_minutes(3)),
-                    repeat: RepeatingStrategy::Once,
-                    visible: true
+                    date_config: Some(Date::now().add_minutes(3).into()),
+                    repeat: Repeat::Once,
+                    visible: true,
+                    ..Default::default()
                 },
                 Task
                 {
                     path: PathBuf::from(name("usb_3")),
                     mask: None,
                     interval: None,
-                    date: Some(Date::now().add_minutes(6)),
-                    repeat: RepeatingStrategy::Once,
-                    visible: true
+                    date_config: Some(Date::now().add_minutes(6).into()),
+                    repeat: Repeat::Once,
+                    visible: true,
+                    ..Default::default()
                 },
                 Task
                 {
                     path: PathBuf::from(name("usb_4")),
                     mask: None,
                     interval: None,
-                    date: Some(Date::now().add_minutes(3)),
-                    repeat: RepeatingStrategy::Dialy,
-                    visible: false
+                    date_config: Some(Date::now().add_minutes(3).into()),
+                    repeat: Repeat::Dialy,
+                    visible: false,
+                    ..Default::default()
                 },
-            ]
+            ],
+            ..Default::default()
         };
         let r = utilites::serialize(cfg, [flash, "config.toml"].concat(), false, utilites::Serializer::Toml);
         //super::main();
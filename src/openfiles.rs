@@ -0,0 +1,73 @@
+//! files opened by running processes, linux only: scanning of `/proc/*/fd`
+use std::{collections::HashSet, path::Path};
+
+///device and inode of the file
+type FileId = (u64, u64);
+
+///snapshot of the files opened by all processes
+#[derive(Debug, Default)]
+pub struct OpenFiles
+{
+    files: HashSet<FileId>
+}
+
+impl OpenFiles
+{
+    #[cfg(target_os = "linux")]
+    pub fn scan() -> Self
+    {
+        use std::os::unix::fs::MetadataExt;
+        let mut files = HashSet::new();
+        if let Ok(processes) = std::fs::read_dir("/proc")
+        {
+            for p in processes.flatten()
+            {
+                //only process directories have numeric names
+                if !p.file_name().to_string_lossy().chars().all(|c| c.is_ascii_digit())
+                {
+                    continue;
+                }
+                if let Ok(fds) = std::fs::read_dir(p.path().join("fd"))
+                {
+                    for fd in fds.flatten()
+                    {
+                        if let Ok(md) = std::fs::metadata(fd.path())
+                        {
+                            if md.is_file()
+                            {
+                                files.insert((md.dev(), md.ino()));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Self
+        {
+            files
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    pub fn scan() -> Self
+    {
+        logger::warn!("Проверка открытых файлов поддерживается только в linux");
+        Self::default()
+    }
+
+    pub fn is_open(&self, path: &Path) -> bool
+    {
+        file_id(path).is_some_and(|id| self.files.contains(&id))
+    }
+}
+
+#[cfg(unix)]
+fn file_id(path: &Path) -> Option<FileId>
+{
+    use std::os::unix::fs::MetadataExt;
+    std::fs::symlink_metadata(path).ok().filter(|m| m.is_file()).map(|m| (m.dev(), m.ino()))
+}
+#[cfg(not(unix))]
+fn file_id(_path: &Path) -> Option<FileId>
+{
+    None
+}
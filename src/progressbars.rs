@@ -103,3 +103,31 @@ pub fn progress_bar_for_datetime(mpb: &MultiProgress, len: u32) -> ProgressBar
     .progress_chars("●●∙");
     pb.with_style(sty)
 }
+
+///bar is filled with the used space of the filesystem
+pub fn progress_bar_for_free_space(mpb: &MultiProgress) -> ProgressBar
+{
+    let pb = mpb.add(ProgressBar::new(0));
+    pb.enable_steady_tick(Duration::from_millis(120));
+    let sty = ProgressStyle::with_template(
+        "[{elapsed_precise}] {prefix} {spinner:.yellow}    {bar:40.red/green} [{msg}]",
+    )
+    .unwrap()
+    .tick_strings(&[
+            "▁",
+            "▂",
+            "▃",
+            "▄",
+            "▅",
+            "▆",
+            "▇",
+            "▆",
+            "▅",
+            "▄",
+            "▃",
+            "▂",
+            "▁",
+    ])
+    .progress_chars("●●∙");
+    pb.with_style(sty)
+}
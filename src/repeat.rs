@@ -0,0 +1,186 @@
+use std::time::Duration;
+use chrono::{Datelike, NaiveDate, NaiveDateTime, TimeDelta};
+use chrono_tz::Tz;
+use scheduler::RepeatingStrategy;
+use crate::{cron::WEEKDAYS, zone};
+
+///task repeat strategy, `weekly` and `yearly` are handled by the calendar instead of the scheduler
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Repeat
+{
+    #[default]
+    Once,
+    Dialy,
+    Forever,
+    Monthly,
+    Weekly,
+    Yearly
+}
+
+impl Repeat
+{
+    pub fn parse(value: &str) -> Option<Self>
+    {
+        match value
+        {
+            "once" => Some(Repeat::Once),
+            "dialy" | "daily" => Some(Repeat::Dialy),
+            "forever" => Some(Repeat::Forever),
+            "monthly" => Some(Repeat::Monthly),
+            "weekly" => Some(Repeat::Weekly),
+            "yearly" => Some(Repeat::Yearly),
+            _ => None
+        }
+    }
+    pub fn as_str(&self) -> &'static str
+    {
+        match self
+        {
+            Repeat::Once => "once",
+            Repeat::Dialy => "dialy",
+            Repeat::Forever => "forever",
+            Repeat::Monthly => "monthly",
+            Repeat::Weekly => "weekly",
+            Repeat::Yearly => "yearly"
+        }
+    }
+    ///strategy for the scheduler and progressbars, calendar strategies are repeated forever
+    pub fn strategy(&self) -> RepeatingStrategy
+    {
+        match self
+        {
+            Repeat::Once => RepeatingStrategy::Once,
+            Repeat::Dialy => RepeatingStrategy::Dialy,
+            Repeat::Forever | Repeat::Weekly | Repeat::Yearly => RepeatingStrategy::Forever,
+            Repeat::Monthly => RepeatingStrategy::Monthly
+        }
+    }
+}
+
+///day of week number from sunday, `mon` -> 1
+pub fn parse_weekday(value: &str) -> Option<u32>
+{
+    let value = value.to_ascii_lowercase();
+    WEEKDAYS.iter().position(|w| *w == value).map(|p| p as u32)
+}
+
+///repeats of date tasks which the scheduler doesn't support
+#[derive(Clone, Copy, Debug)]
+pub struct Calendar
+{
+    repeat: Repeat,
+    ///bits of days of week from sunday
+    weekdays: u8,
+    every: Option<Duration>,
+    ///runs follow the wall time of the zone including DST transitions
+    zone: Option<Tz>
+}
+
+impl Calendar
+{
+    ///calendar is used only with `weekly`, `yearly`, `weekdays`, `every` or repeating task with the time zone
+    pub fn new(repeat: Repeat, weekdays: &[String], every: Option<Duration>, zone: Option<Tz>) -> Option<Self>
+    {
+        let weekdays = weekdays.iter()
+            .filter_map(|w| parse_weekday(w))
+            .fold(0u8, |mask, w| mask | 1 << w);
+        let zoned = zone.is_some() && repeat != Repeat::Once;
+        if zoned || every.is_some() || weekdays != 0 || matches!(repeat, Repeat::Weekly | Repeat::Yearly)
+        {
+            Some(Self { repeat, weekdays, every, zone })
+        }
+        else
+        {
+            None
+        }
+    }
+
+    ///the nearest run strictly after `now`, `start` is the date from the task settings,
+    ///both are in the local time of this computer
+    pub fn next_after(&self, start: &NaiveDateTime, now: &NaiveDateTime) -> Option<NaiveDateTime>
+    {
+        match self.zone
+        {
+            Some(z) =>
+            {
+                let next = self.next_wall(&zone::to_zone(start, z)?, &zone::to_zone(now, z)?)?;
+                zone::from_zone(&next, z)
+            },
+            None => self.next_wall(start, now)
+        }
+    }
+
+    fn next_wall(&self, start: &NaiveDateTime, now: &NaiveDateTime) -> Option<NaiveDateTime>
+    {
+        if let Some(every) = self.every
+        {
+            return step(start, now, TimeDelta::from_std(every).ok()?);
+        }
+        if self.weekdays != 0
+        {
+            let mut day = start.date().max(now.date());
+            //the same day may be already passed, so a week and a day are checked
+            for _ in 0..8
+            {
+                let t = day.and_time(start.time());
+                if t > *now && t >= *start && self.weekdays & 1 << day.weekday().num_days_from_sunday() != 0
+                {
+                    return Some(t);
+                }
+                day = day.succ_opt()?;
+            }
+            return None;
+        }
+        match self.repeat
+        {
+            Repeat::Dialy | Repeat::Forever => step(start, now, TimeDelta::days(1)),
+            Repeat::Weekly => step(start, now, TimeDelta::weeks(1)),
+            Repeat::Monthly =>
+            {
+                //months before the current one are always passed
+                let (mut year, mut month) = (start.year(), start.month()).max((now.year(), now.month()));
+                for _ in 0..13
+                {
+                    let t = clamped_date(year, month, start.day())?.and_time(start.time());
+                    if t > *now && t >= *start
+                    {
+                        return Some(t);
+                    }
+                    (year, month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+                }
+                None
+            },
+            Repeat::Yearly =>
+            {
+                //29 february is moved to 28 in not leap years
+                (start.year().max(now.year() - 1)..=start.year().max(now.year()) + 1)
+                    .filter_map(|y| clamped_date(y, start.month(), start.day()))
+                    .map(|d| d.and_time(start.time()))
+                    .find(|t| t > now && t >= start)
+            },
+            Repeat::Once => None
+        }
+    }
+}
+
+///the day of the month or the last day of the shorter month
+fn clamped_date(year: i32, month: u32, day: u32) -> Option<NaiveDate>
+{
+    (0..4).find_map(|d| NaiveDate::from_ymd_opt(year, month, day.saturating_sub(d)))
+}
+
+///`start` plus the minimal count of periods which is after `now`
+fn step(start: &NaiveDateTime, now: &NaiveDateTime, period: TimeDelta) -> Option<NaiveDateTime>
+{
+    if start > now
+    {
+        return Some(*start);
+    }
+    let period = period.num_seconds();
+    if period <= 0
+    {
+        return None;
+    }
+    let elapsed = (*now - *start).num_seconds();
+    Some(*start + TimeDelta::seconds((elapsed / period + 1) * period))
+}
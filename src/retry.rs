@@ -0,0 +1,67 @@
+use std::{future::Future, sync::atomic::{AtomicU32, Ordering}, time::Duration};
+use serde::{Deserialize, Serialize};
+
+///repeat processing of busy or locked files, delay between attempts grows up to `max_delay`
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct Retry
+{
+    #[serde(default = "default_attempts")]
+    pub attempts: u32,
+    #[serde(default = "default_delay")]
+    #[serde(with = "crate::helpers::duration")]
+    pub delay: Duration,
+    #[serde(default = "default_max_delay")]
+    #[serde(with = "crate::helpers::duration")]
+    pub max_delay: Duration
+}
+
+fn default_attempts() -> u32
+{
+    5
+}
+fn default_delay() -> Duration
+{
+    Duration::from_secs(1)
+}
+fn default_max_delay() -> Duration
+{
+    Duration::from_secs(30)
+}
+
+///file is busy or locked by another program, the operation can be repeated later
+pub fn is_retryable(error: &std::io::Error) -> bool
+{
+    //ERROR_SHARING_VIOLATION, ERROR_LOCK_VIOLATION
+    let locked = cfg!(windows) && matches!(error.raw_os_error(), Some(32 | 33));
+    locked || matches!(error.kind(), std::io::ErrorKind::PermissionDenied | std::io::ErrorKind::ResourceBusy)
+}
+
+///run `operation` with retries of busy or locked errors, `on_retry` receive the number of the next attempt
+pub async fn with_retry<F, Fut, R>(settings: &Retry, operation: F, on_retry: R) -> std::io::Result<()>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = std::io::Result<()>>,
+    R: Fn(u32, u32)
+{
+    let attempt = AtomicU32::new(0);
+    let attempts = settings.attempts.max(1);
+    //not retryable errors are returned as successful result to stop retries
+    let result = utilites::retry(attempts as _, settings.delay.as_millis() as _, settings.max_delay.as_millis() as _, ||
+    {
+        let n = attempt.fetch_add(1, Ordering::SeqCst) + 1;
+        if n > 1
+        {
+            on_retry(n, attempts);
+        }
+        let fut = operation();
+        async move
+        {
+            match fut.await
+            {
+                Err(e) if is_retryable(&e) => Err(e),
+                other => Ok(other)
+            }
+        }
+    }).await;
+    result.and_then(|r| r)
+}
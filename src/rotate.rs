@@ -0,0 +1,183 @@
+use std::{fs::File, path::{Path, PathBuf}};
+use serde::{Deserialize, Serialize};
+
+///logrotate-like rotation: `app.log` -> `app.log.1` -> `app.log.2` ... up to `keep` generations
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct Rotate
+{
+    ///count of kept generations
+    #[serde(default = "default_keep")]
+    pub keep: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compress: Option<Compression>,
+    ///create new empty file in place of the rotated one
+    #[serde(default = "default_create")]
+    pub create: bool
+}
+impl Default for Rotate
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            keep: default_keep(),
+            compress: None,
+            create: default_create()
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression
+{
+    Gzip,
+    Zstd
+}
+impl Compression
+{
+    fn extension(&self) -> &'static str
+    {
+        match self
+        {
+            Compression::Gzip => "gz",
+            Compression::Zstd => "zst"
+        }
+    }
+}
+
+fn default_keep() -> u32
+{
+    5
+}
+fn default_create() -> bool
+{
+    true
+}
+
+const EXTENSIONS: [&str; 2] = ["gz", "zst"];
+
+///generation number from the rotated file name, `app.log.3.gz` -> (`app.log`, 3)
+pub fn parse_generation(name: &str) -> Option<(&str, u32)>
+{
+    let name = EXTENSIONS.iter()
+        .find_map(|e| name.strip_suffix(&[".", e].concat()))
+        .unwrap_or(name);
+    let (base, n) = name.rsplit_once('.')?;
+    if n.is_empty() || !n.chars().all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+    Some((base, n.parse().ok()?))
+}
+
+///file is a rotated generation and must not be rotated itself,
+///`dump.2025` is a generation only if `dump` exists next to it
+pub fn is_generation(path: &Path) -> bool
+{
+    path.file_name()
+        .and_then(|n| parse_generation(&n.to_string_lossy()).map(|(base, _)| path.with_file_name(base)))
+        .is_some_and(|base| base.exists())
+}
+
+///rotate the file, returns path of the newest generation
+pub async fn rotate_file(path: &Path, settings: &Rotate) -> std::io::Result<PathBuf>
+{
+    let path = path.to_path_buf();
+    let settings = settings.clone();
+    tokio::task::spawn_blocking(move || rotate(&path, &settings))
+        .await
+        .map_err(std::io::Error::other)?
+}
+
+pub fn rotate(path: &Path, settings: &Rotate) -> std::io::Result<PathBuf>
+{
+    let keep = settings.keep.max(1);
+    let name = path.file_name()
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, "некорректное имя файла"))?
+        .to_string_lossy()
+        .to_string();
+    let dir = path.parent().unwrap_or(Path::new("."));
+    //existing generations with their numbers
+    let mut generations: Vec<(u32, PathBuf)> = std::fs::read_dir(dir)?
+        .flatten()
+        .filter_map(|e|
+        {
+            let file_name = e.file_name().to_string_lossy().to_string();
+            parse_generation(&file_name)
+                .filter(|(base, _)| *base == name)
+                .map(|(_, n)| (n, e.path()))
+        })
+        .collect();
+    //the oldest go first, so shifting doesn't overwrite anything
+    generations.sort_by(|a, b| b.0.cmp(&a.0));
+    for (n, g) in generations
+    {
+        if n >= keep
+        {
+            std::fs::remove_file(&g)?;
+        }
+        else
+        {
+            let g_name = g.file_name().unwrap_or_default().to_string_lossy().to_string();
+            let ext = EXTENSIONS.iter().find(|e| g_name.ends_with(&[".", e].concat()));
+            std::fs::rename(&g, generation(dir, &name, n + 1, ext.copied()))?;
+        }
+    }
+    let first = generation(dir, &name, 1, None);
+    std::fs::rename(path, &first)?;
+    if settings.create
+    {
+        let file = File::create(path)?;
+        if let Ok(md) = std::fs::metadata(&first)
+        {
+            let _ = file.set_permissions(md.permissions());
+        }
+    }
+    if let Some(compression) = settings.compress
+    {
+        let compressed = generation(dir, &name, 1, Some(compression.extension()));
+        compress(&first, &compressed, compression)?;
+        std::fs::remove_file(&first)?;
+        return Ok(compressed);
+    }
+    Ok(first)
+}
+
+fn generation(dir: &Path, name: &str, n: u32, ext: Option<&str>) -> PathBuf
+{
+    if let Some(ext) = ext
+    {
+        dir.join([name, ".", &n.to_string(), ".", ext].concat())
+    }
+    else
+    {
+        dir.join([name, ".", &n.to_string()].concat())
+    }
+}
+
+fn compress(source: &Path, destination: &Path, compression: Compression) -> std::io::Result<()>
+{
+    let mut source = File::open(source)?;
+    let destination_file = File::create_new(destination)?;
+    let result = match compression
+    {
+        Compression::Gzip =>
+        {
+            let mut encoder = flate2::write::GzEncoder::new(destination_file, flate2::Compression::default());
+            std::io::copy(&mut source, &mut encoder)
+                .and_then(|_| encoder.finish())
+                .and_then(|f| f.sync_all())
+        },
+        Compression::Zstd =>
+        {
+            zstd::stream::copy_encode(&mut source, &destination_file, 0)
+                .and_then(|_| destination_file.sync_all())
+        }
+    };
+    if result.is_err()
+    {
+        let _ = std::fs::remove_file(destination);
+    }
+    result
+}
@@ -0,0 +1,82 @@
+use std::{io::SeekFrom, path::{Path, PathBuf}};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+///overwrite file contents before unlinking
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct Shred
+{
+    ///number of overwrite passes
+    #[serde(default = "default_passes")]
+    pub passes: u32,
+    #[serde(default)]
+    pub pattern: ShredPattern,
+    ///rename file to random name after overwriting
+    #[serde(default)]
+    pub rename: bool
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ShredPattern
+{
+    #[default]
+    Zero,
+    Random
+}
+
+fn default_passes() -> u32
+{
+    3
+}
+
+impl Shred
+{
+    ///total bytes which will be written for file with `len` size
+    pub fn bytes_for(&self, len: u64) -> u64
+    {
+        len * self.passes.max(1) as u64
+    }
+}
+
+///overwrite file `passes` times, `on_progress` receive count of written bytes
+///returns the path of file after optional rename
+pub async fn shred_file<F: Fn(u64)>(path: &Path, settings: &Shred, on_progress: F) -> std::io::Result<PathBuf>
+{
+    let len = tokio::fs::metadata(path).await?.len();
+    let mut file = tokio::fs::OpenOptions::new().write(true).open(path).await?;
+    let mut buf = vec![0u8; CHUNK_SIZE];
+    for _ in 0..settings.passes.max(1)
+    {
+        file.seek(SeekFrom::Start(0)).await?;
+        let mut written = 0;
+        while written < len
+        {
+            let n = std::cmp::min(CHUNK_SIZE as u64, len - written) as usize;
+            if settings.pattern == ShredPattern::Random
+            {
+                rand::rng().fill_bytes(&mut buf[..n]);
+            }
+            file.write_all(&buf[..n]).await?;
+            written += n as u64;
+            on_progress(n as u64);
+        }
+        file.flush().await?;
+        file.sync_all().await?;
+    }
+    drop(file);
+    if settings.rename
+    {
+        let name: String = (0..16).map(|_| format!("{:02x}", rand::random::<u8>())).collect();
+        let new_path = path.with_file_name(name);
+        tokio::fs::rename(path, &new_path).await?;
+        Ok(new_path)
+    }
+    else
+    {
+        Ok(path.to_path_buf())
+    }
+}
@@ -1,8 +1,9 @@
-use std::{borrow::Cow,  path::{Path, PathBuf}};
-use crate::helpers::time_diff;
-use indicatif::{MultiProgress, ProgressBar};
+use std::{borrow::Cow, collections::HashSet, path::{Path, PathBuf}, time::Duration};
+use crate::{archive::{self, Archive}, cron::Cron, repeat::{self, Calendar, Repeat}, zone::{self, ZonedDate}, audit::{self, AuditEntry}, freespace::{self, Threshold, Usage}, rotate::{self, Rotate}, truncate, guard::Guard, hooks::Hook, openfiles::OpenFiles, retry::{self, Retry}, verify, filters::{self, Globs, KeepBy, RegexOn, SymlinkPolicy, TimeKind}, helpers::{format_duration, parse_duration, time_diff}, shred::{self, Shred}, targets::{self, error_reason, Report, Selection, Target}, trash};
+use indicatif::{HumanBytes, MultiProgress, ProgressBar};
 use scheduler::RepeatingStrategy;
 use serde::{Deserialize, Serialize, Serializer};
+use chrono_tz::Tz;
 use utilites::Date;
 
 #[derive(Deserialize, Serialize, Clone, Debug, Eq)]
@@ -11,16 +12,223 @@ pub struct Task
     pub path: PathBuf,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub mask: Option<String>,
+    ///timer interval `45s`, `1h30m`, `2d`, bare integer is a count of minutes
+    #[serde(deserialize_with="deserialize_interval", serialize_with="serialize_interval")]
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub interval: Option<u32>,
-    #[serde(deserialize_with="deserialize_data")]
+    pub interval: Option<Duration>,
+    ///date as written in the config, converted into `date` by `localize`
+    #[serde(rename = "date", deserialize_with="deserialize_data", serialize_with="serialize_data")]
     #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date_config: Option<ZonedDate>,
+    ///date in the local time of this computer
+    #[serde(skip)]
     pub date: Option<Date>,
+    ///time zone of `date` without offset and of its daily and monthly cycles, `Europe/Moscow`
+    #[serde(deserialize_with="zone::deserialize_timezone", serialize_with="zone::serialize_timezone")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<Tz>,
+    ///cron expression `minute hour day month weekday`, alternative to `interval` and `date`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cron: Option<Cron>,
     #[serde(serialize_with="serialize_repeating")]
     #[serde(deserialize_with="deserialize_repeating")]
-    pub repeat: RepeatingStrategy,
+    pub repeat: Repeat,
+    ///days of week of the date task `["mon", "thu"]`, the task runs at the time from `date`
+    #[serde(deserialize_with="deserialize_weekdays")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub weekdays: Vec<String>,
+    ///period of the date task `3d`, `12h`, counted from `date`
+    #[serde(with = "crate::helpers::duration_option")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub every: Option<Duration>,
     #[serde(default)]
-    pub visible: bool
+    pub visible: bool,
+    ///only report what would be removed, without touching the disk
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub dry_run: bool,
+    ///overwrite every removed file before unlinking
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shred: Option<Shred>,
+    #[serde(default, skip_serializing_if = "Mode::is_default")]
+    pub mode: Mode,
+    ///pack files into the archive before processing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archive: Option<Archive>,
+    ///process only files older than this duration, `7d`, `12h`, `1h30m`
+    #[serde(with = "crate::helpers::duration_option")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub older_than: Option<Duration>,
+    ///process only files newer than this duration
+    #[serde(with = "crate::helpers::duration_option")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub newer_than: Option<Duration>,
+    ///file timestamp for `older_than` and `newer_than`
+    #[serde(default, skip_serializing_if = "TimeKind::is_default")]
+    pub age_by: TimeKind,
+    ///directory size limit `20GiB`, `500MB`, the oldest selected files are removed until directory fits under it
+    #[serde(with = "crate::helpers::size_option")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_size: Option<u64>,
+    ///limit of files count in the directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_files: Option<u64>,
+    ///keep the newest N selected files, process the rest
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep_last: Option<usize>,
+    ///order of files for `keep_last`
+    #[serde(default, skip_serializing_if = "KeepBy::is_default")]
+    pub keep_by: KeepBy,
+    ///glob patterns of processed files inside of the directory, `**` matches any subdirectories
+    #[serde(deserialize_with="deserialize_globs")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+    ///glob patterns of files which are never processed
+    #[serde(deserialize_with="deserialize_globs")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+    ///regular expression for processed files inside of the directory and its subdirectories
+    #[serde(deserialize_with="deserialize_regex")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub regex: Option<String>,
+    ///match `regex` against file name or relative path
+    #[serde(default, skip_serializing_if = "RegexOn::is_default")]
+    pub regex_on: RegexOn,
+    ///symbolic links handling: `delete_link`, `skip` or `follow`
+    #[serde(default, skip_serializing_if = "SymlinkPolicy::is_default")]
+    pub symlinks: SymlinkPolicy,
+    ///remove directories left empty after processing of the selected files
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub prune_empty_dirs: bool,
+    ///remove the task directory too, if it is left empty
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub prune_root: bool,
+    ///repeat processing of busy or locked files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<Retry>,
+    ///expected BLAKE3 digest of the file, the task fails if the content differs
+    #[serde(deserialize_with="deserialize_digest")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blake3: Option<String>,
+    ///manifest of digests in `b3sum` format for directories, paths are relative to the task directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blake3_manifest: Option<PathBuf>,
+    ///skip files opened by any process (linux only), repeating tasks try them again on the next cycle
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub skip_open_files: bool,
+    ///command executed before processing, the task is aborted if it fails
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub before: Option<Hook>,
+    ///command executed after processing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub after: Option<Hook>,
+    ///`truncate` mode: keep the last bytes of the file
+    #[serde(with = "crate::helpers::size_option")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep_bytes: Option<u64>,
+    ///`truncate` mode: keep the last lines of the file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep_lines: Option<usize>,
+    ///`rotate` mode settings, `rotate = 5` is a short form of `rotate = { keep = 5 }`
+    #[serde(deserialize_with="deserialize_rotate")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rotate: Option<Rotate>,
+    ///run the task when free space of the filesystem with `path` drops below `5GiB` or `10%`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub when_free_below: Option<Threshold>
+}
+
+///what to do with the files selected by the task
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode
+{
+    ///permanent deletion
+    #[default]
+    Delete,
+    ///move into the trash by freedesktop.org specification
+    Trash,
+    ///truncate files in place, keeping `keep_bytes` or `keep_lines` from the end
+    Truncate,
+    ///rotate files like logrotate with settings from `rotate` block
+    Rotate
+}
+impl Mode
+{
+    pub fn is_default(&self) -> bool
+    {
+        *self == Mode::default()
+    }
+    ///action name for the task status
+    pub fn past_tense(&self) -> &'static str
+    {
+        match self
+        {
+            Mode::Delete => "удалено",
+            Mode::Trash => "перемещено в корзину",
+            Mode::Truncate => "усечено",
+            Mode::Rotate => "ротировано"
+        }
+    }
+    ///mode name for the audit log
+    pub fn name(&self) -> &'static str
+    {
+        match self
+        {
+            Mode::Delete => "delete",
+            Mode::Trash => "trash",
+            Mode::Truncate => "truncate",
+            Mode::Rotate => "rotate"
+        }
+    }
+}
+
+impl Default for Task
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            path: PathBuf::new(),
+            mask: None,
+            interval: None,
+            date_config: None,
+            date: None,
+            timezone: None,
+            repeat: Repeat::Once,
+            weekdays: Vec::new(),
+            every: None,
+            visible: false,
+            dry_run: false,
+            shred: None,
+            mode: Mode::Delete,
+            archive: None,
+            older_than: None,
+            newer_than: None,
+            age_by: TimeKind::Mtime,
+            max_size: None,
+            max_files: None,
+            keep_last: None,
+            keep_by: KeepBy::Mtime,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            regex: None,
+            regex_on: RegexOn::Name,
+            symlinks: SymlinkPolicy::DeleteLink,
+            prune_empty_dirs: false,
+            prune_root: false,
+            retry: None,
+            blake3: None,
+            blake3_manifest: None,
+            skip_open_files: false,
+            before: None,
+            after: None,
+            keep_bytes: None,
+            keep_lines: None,
+            rotate: None,
+            when_free_below: None,
+            cron: None
+        }
+    }
 }
 
 impl PartialEq for Task
@@ -29,6 +237,9 @@ impl PartialEq for Task
     {
         &self.path == &other.path
         && &self.mask == &other.mask
+        && &self.include == &other.include
+        && &self.exclude == &other.exclude
+        && &self.regex == &other.regex
     }
 }
 
@@ -42,6 +253,41 @@ impl Task
     {
         &self.path.as_os_str().to_str().unwrap_or_default()
     }
+    ///calendar of the date task with repeats not supported by the scheduler
+    pub fn calendar(&self) -> Option<Calendar>
+    {
+        self.date.as_ref()?;
+        Calendar::new(self.repeat, &self.weekdays, self.every, self.timezone)
+    }
+    ///settings which depend on each other, checked when the config is loaded
+    pub fn validate(&self) -> Result<(), String>
+    {
+        if cfg!(not(unix)) && self.mode == Mode::Trash
+        {
+            return Err("Режим `trash` поддерживается только в unix системах".to_owned());
+        }
+        if cfg!(not(target_os = "linux")) && self.when_free_below.is_some()
+        {
+            return Err("Параметр `when_free_below` поддерживается только в linux".to_owned());
+        }
+        if self.path.is_file() && (self.max_size.is_some() || self.max_files.is_some() || self.keep_last.is_some())
+        {
+            return Err(["Параметры `max_size`, `max_files` и `keep_last` применимы только к директориям, `", self.get_str_path(), "` является файлом"].concat());
+        }
+        if let Some(archive) = self.archive.as_ref()
+        {
+            archive.check_destination(&self.path)?;
+        }
+        Ok(())
+    }
+    ///convert the date from the config into the local time of this computer
+    pub fn localize(&mut self)
+    {
+        if let Some(d) = self.date_config.as_ref()
+        {
+            self.date = d.to_local(self.timezone);
+        }
+    }
     pub fn get_hash(&self) -> String
     {
         let mut hasher = blake3::Hasher::new();
@@ -50,6 +296,10 @@ impl Task
         {
             hasher.update(mask.as_bytes());
         }
+        for pattern in self.include.iter().chain(self.exclude.iter()).chain(self.regex.iter())
+        {
+            hasher.update(pattern.as_bytes());
+        }
         let res = hasher.finalize();
         let string = format!("{}", res.to_hex());
         string
@@ -57,12 +307,12 @@ impl Task
 }
 
 
-fn deserialize_data<'de, D>(deserializer: D) -> Result<Option<Date>, D::Error>
+fn deserialize_data<'de, D>(deserializer: D) -> Result<Option<ZonedDate>, D::Error>
 where
     D: serde::de::Deserializer<'de>,
 {
     let s: String = serde::de::Deserialize::deserialize(deserializer)?;
-    if let Some(date) = Date::parse(&s)
+    if let Some(date) = ZonedDate::parse(&s)
     {
         Ok(Some(date))
     }
@@ -72,33 +322,158 @@ where
     }
 }
 
-fn deserialize_repeating<'de, D>(deserializer: D) -> Result<RepeatingStrategy, D::Error>
+fn serialize_data<S>(date: &Option<ZonedDate>, serializer: S) -> Result<S::Ok, S::Error> 
+where 
+    S: Serializer,
+{
+    match date
+    {
+        Some(d) => serializer.serialize_str(&d.to_string()),
+        None => serializer.serialize_none()
+    }
+}
+
+fn deserialize_globs<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::de::Deserializer<'de>,
+{
+    let patterns: Vec<String> = serde::de::Deserialize::deserialize(deserializer)?;
+    for p in &patterns
+    {
+        if let Err(e) = glob::Pattern::new(p)
+        {
+            return Err(serde::de::Error::custom(["Ошибка шаблона `", p, "`: ", &e.to_string()].concat()));
+        }
+    }
+    Ok(patterns)
+}
+
+fn deserialize_regex<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
 where
     D: serde::de::Deserializer<'de>,
 {
     let s: String = serde::de::Deserialize::deserialize(deserializer)?;
-    match s.as_str()
+    if let Err(e) = regex::Regex::new(&s)
+    {
+        Err(serde::de::Error::custom(["Ошибка регулярного выражения `", &s, "`: ", &e.to_string()].concat()))
+    }
+    else 
+    {
+        Ok(Some(s))
+    }
+}
+
+fn deserialize_digest<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::de::Deserializer<'de>,
+{
+    let s: String = serde::de::Deserialize::deserialize(deserializer)?;
+    if verify::is_valid_digest(&s)
+    {
+        Ok(Some(s.to_ascii_lowercase()))
+    }
+    else 
     {
-        "monthly" => Ok(RepeatingStrategy::Monthly),
-        "dialy" => Ok(RepeatingStrategy::Dialy),
-        "forever" => Ok(RepeatingStrategy::Forever),
-        "once" => Ok(RepeatingStrategy::Once),
-        _ => Err(serde::de::Error::custom(["Ошибка, опции `" , &s, "` не существует"].concat()))
+        Err(serde::de::Error::custom(["Ошибка формата BLAKE3 `", &s, "`, ожидается 64 шестнадцатеричных символа"].concat()))
     }
 }
 
-fn serialize_repeating<S>(repeat: &RepeatingStrategy, serializer: S) -> Result<S::Ok, S::Error> 
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum IntervalValue
+{
+    Minutes(u32),
+    Text(String)
+}
+
+fn deserialize_interval<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+where
+    D: serde::de::Deserializer<'de>,
+{
+    let interval = match IntervalValue::deserialize(deserializer)?
+    {
+        IntervalValue::Minutes(m) => Some(Duration::from_secs(m as u64 * 60)),
+        IntervalValue::Text(s) => parse_duration(&s)
+            .or_else(|| s.trim().parse::<u32>().ok().map(|m| Duration::from_secs(m as u64 * 60)))
+            .ok_or_else(|| serde::de::Error::custom(["Ошибка формата интервала ", &s].concat()))?
+            .into()
+    };
+    if interval.is_some_and(|i| i.as_secs() == 0)
+    {
+        return Err(serde::de::Error::custom("Интервал должен быть не меньше секунды"));
+    }
+    Ok(interval)
+}
+
+///whole minutes are written as a number for compatibility
+fn serialize_interval<S>(interval: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error> 
 where 
     S: Serializer,
 {
-    serializer.serialize_str(&repeat.to_string())
+    match interval
+    {
+        Some(i) if i.as_secs() % 60 == 0 => serializer.serialize_u64(i.as_secs() / 60),
+        Some(i) => serializer.serialize_str(&format_duration(i)),
+        None => serializer.serialize_none()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RotateValue
+{
+    Keep(u32),
+    Settings(Rotate)
+}
+
+fn deserialize_rotate<'de, D>(deserializer: D) -> Result<Option<Rotate>, D::Error>
+where
+    D: serde::de::Deserializer<'de>,
+{
+    let rotate = match RotateValue::deserialize(deserializer)?
+    {
+        RotateValue::Keep(keep) => Rotate { keep, ..Default::default() },
+        RotateValue::Settings(settings) => settings
+    };
+    Ok(Some(rotate))
+}
+
+fn deserialize_repeating<'de, D>(deserializer: D) -> Result<Repeat, D::Error>
+where
+    D: serde::de::Deserializer<'de>,
+{
+    let s: String = serde::de::Deserialize::deserialize(deserializer)?;
+    Repeat::parse(&s).ok_or_else(|| serde::de::Error::custom(["Ошибка, опции `" , &s, "` не существует"].concat()))
+}
+
+fn serialize_repeating<S>(repeat: &Repeat, serializer: S) -> Result<S::Ok, S::Error> 
+where 
+    S: Serializer,
+{
+    serializer.serialize_str(repeat.as_str())
+}
+
+fn deserialize_weekdays<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::de::Deserializer<'de>,
+{
+    let days: Vec<String> = serde::de::Deserialize::deserialize(deserializer)?;
+    days.into_iter().map(|d|
+    {
+        repeat::parse_weekday(&d)
+            .map(|_| d.to_ascii_lowercase())
+            .ok_or_else(|| serde::de::Error::custom(["Ошибка, дня недели `" , &d, "` не существует"].concat()))
+    }).collect()
 }
 
 #[derive(Clone, Debug)]
 pub struct TaskWithProgress
 {
     task: Task,
-    pb: ProgressBar
+    pb: ProgressBar,
+    guard: Guard,
+    ///date from the task settings, calendar repeats are counted from it
+    start: Option<Date>
 }
 impl PartialEq for TaskWithProgress
 {
@@ -109,22 +484,36 @@ impl PartialEq for TaskWithProgress
 }
 impl TaskWithProgress
 {
-    pub fn new(task: Task, mpb: &MultiProgress) -> Self
+    pub fn new(task: Task, mpb: &MultiProgress, guard: Guard) -> Self
     {
         let pb = if std::fs::exists(&task.path).is_ok_and(|f| f == true)
         {
-            if let Some(d) = task.date.as_ref()
+            if let Some(threshold) = task.when_free_below.as_ref()
+            {
+                let pb = crate::progress_bar_for_free_space(mpb);
+                Self::set_free_space_message(&pb, task.visible, threshold, &task.path, task.mask.as_ref());
+                pb.set_prefix("💾");
+                pb
+            }
+            else if task.cron.is_some() || task.calendar().is_some()
+            {
+                //length and message are set by the first `schedule_next`
+                let pb = crate::progress_bar_for_datetime(mpb, 0);
+                pb.set_prefix("⌛");
+                pb
+            }
+            else if let Some(d) = task.date.as_ref()
             {
                 let now = Date::now();
                 let target = time_diff(&now, &d);
                 let pb = crate::progress_bar_for_datetime(mpb, target as u32);
-                Self::set_date_message(&pb, task.visible, d, &task.path,task.mask.as_ref(), &task.repeat);
+                Self::set_date_message(&pb, task.visible, d, &task.path,task.mask.as_ref(), &task.repeat.strategy());
                 pb
             }
             else if let Some(i) = task.interval
             {
-                let pb = crate::progress_bar_for_interval(mpb, &task.repeat, i);
-                Self::set_interval_message(&pb, task.visible, &task.path,task.mask.as_ref(), &task.repeat);
+                let pb = crate::progress_bar_for_interval(mpb, &task.repeat.strategy(), i.as_secs() as u32);
+                Self::set_interval_message(&pb, task.visible, &task.path,task.mask.as_ref(), &task.repeat.strategy());
                 pb
             }
             else
@@ -142,14 +531,65 @@ impl TaskWithProgress
         };
         Self
         {
+            start: task.date.clone(),
             task,
-            pb
+            pb,
+            guard
         }
     }
-    pub fn get_interval(&self) -> Option<u32>
+    pub fn get_interval(&self) -> Option<Duration>
     {
         self.task.interval
     }
+    pub fn get_free_space_threshold(&self) -> Option<Threshold>
+    {
+        self.task.when_free_below
+    }
+    ///refresh free space in the progressbar, returns current usage of the filesystem
+    pub fn update_free_space(&self) -> Option<Usage>
+    {
+        let threshold = self.task.when_free_below.as_ref()?;
+        Self::set_free_space_message(&self.pb, self.task.visible, threshold, self.get_path(), self.task.mask.as_ref())
+    }
+    ///task is scheduled by `cron`, calendar repeats or interval with seconds instead of the scheduler,
+    ///the scheduler counts intervals only in whole minutes
+    pub fn is_self_scheduled(&self) -> bool
+    {
+        self.task.cron.is_some() 
+        || self.task.calendar().is_some()
+        || self.task.interval.is_some_and(|i| i.as_secs() % 60 != 0)
+    }
+    ///move the task to the next run by `cron`, calendar repeats or interval and show it in the progressbar, returns seconds until it
+    pub fn schedule_next(&mut self) -> Option<u64>
+    {
+        if self.task.cron.is_none() && self.task.calendar().is_none()
+        {
+            let len = self.task.interval?.as_secs().max(1);
+            self.reset();
+            self.pb.set_length(len);
+            self.pb.set_position(0);
+            Self::set_interval_message(&self.pb, self.task.visible, self.get_path(), self.task.mask.as_ref(), &self.get_strategy());
+            return Some(len);
+        }
+        let now = Date::now();
+        let next = if let Some(cron) = self.task.cron.as_ref()
+        {
+            cron.next_after(&now.as_naive_datetime())?
+        }
+        else 
+        {
+            let start = self.start.as_ref()?.as_naive_datetime();
+            self.task.calendar()?.next_after(&start, &now.as_naive_datetime())?
+        };
+        let next = Date::parse(&next.format("%Y-%m-%dT%H:%M:%S").to_string())?;
+        let len = time_diff(&now, &next).max(1) as u64;
+        self.reset();
+        self.pb.set_length(len);
+        self.pb.set_position(0);
+        Self::set_date_message(&self.pb, self.task.visible, &next, self.get_path(), self.task.mask.as_ref(), &self.get_strategy());
+        self.task.date = Some(next);
+        Some(len)
+    }
     pub fn get_date(&self) -> Option<Date>
     {
         self.task.date.clone()
@@ -167,9 +607,9 @@ impl TaskWithProgress
         &self.task.get_path()
     }
     
-    pub fn get_strategy(&self) -> &RepeatingStrategy
+    pub fn get_strategy(&self) -> RepeatingStrategy
     {
-        &self.task.repeat
+        self.task.repeat.strategy()
     }
     pub fn set_prefix(&self, prefix: impl Into<Cow<'static, str>>)
     {
@@ -180,12 +620,48 @@ impl TaskWithProgress
     {
         self.pb.println(message);
     }
+    pub fn is_dry_run(&self) -> bool
+    {
+        self.task.dry_run
+    }
+    ///finish progressbar work with partial failures
+    pub fn finish_with_warning(&self)
+    {
+        self.set_prefix("⚠️");
+        self.pb.finish();
+    }
     ///finish progressbar work
     pub fn finish(&self)
     {
-        self.set_prefix("✅");
+        if self.is_dry_run()
+        {
+            self.set_prefix("🧪");
+        }
+        else
+        {
+            self.set_prefix("✅");
+        }
         self.pb.finish();
     }
+    ///show the result of the repeating task cycle, the progressbar keeps running,
+    ///the prefix is reset by the next cycle
+    pub fn finish_cycle(&self, result: Result<Report, String>)
+    {
+        match result
+        {
+            Ok(report) if report.has_warnings() => self.set_prefix("⚠️"),
+            Ok(_) if self.is_dry_run() => self.set_prefix("🧪"),
+            //free space tasks have no cycle messages which would reset it
+            Ok(_) if self.task.when_free_below.is_some() => self.set_prefix("💾"),
+            Ok(_) => (),
+            Err(e) =>
+            {
+                logger::error!("{}", &e);
+                self.set_prefix("❌");
+                self.print_line(e);
+            }
+        }
+    }
     pub fn finish_with_err<P: AsRef<str>>(&self, err: P)
     {
         self.set_prefix("❌");
@@ -205,7 +681,7 @@ impl TaskWithProgress
             {
                 self.pb.set_length(len as u64);
                 let new_date = self.task.date.as_ref().unwrap().clone().add_seconds(len as i64);
-                Self::set_date_message(&self.pb, self.task.visible, &new_date, self.get_path(), self.task.mask.as_ref(), self.get_strategy());
+                Self::set_date_message(&self.pb, self.task.visible, &new_date, self.get_path(), self.task.mask.as_ref(), &self.get_strategy());
             }
             self.pb.set_position(current);
         }
@@ -223,81 +699,693 @@ impl TaskWithProgress
             self.pb.set_length(len as u64);
             self.pb.set_position(current);
             let new_date = self.task.date.as_ref().unwrap().clone().add_seconds(len as i64);
-            Self::set_date_message(&self.pb, self.task.visible, &new_date, self.get_path(), self.task.mask.as_ref(), self.get_strategy());
+            Self::set_date_message(&self.pb, self.task.visible, &new_date, self.get_path(), self.task.mask.as_ref(), &self.get_strategy());
             self.task.date = Some(new_date);
         }
     }
 
-    pub async fn del_file(&self) -> Result<(), String>
+    ///process the task with `before` and `after` hooks
+    pub async fn run(&self, task_id: &str) -> Result<Report, String>
+    {
+        let str_path = self.get_str_path();
+        if self.is_dry_run()
+        {
+            for h in self.task.before.iter().chain(self.task.after.iter())
+            {
+                let msg = ["[dry-run] команда `", &h.command_line(), "` не выполняется"].concat();
+                logger::info!("{}", &msg);
+                self.print_line(msg);
+            }
+            return self.del_file().await;
+        }
+        if let Some(hook) = self.task.before.as_ref()
+        {
+            hook.run(self.get_path(), task_id, None).await
+                .map_err(|e| ["Ошибка команды before для `", str_path, "`, удаление отменено: ", &e].concat())?;
+        }
+        let result = self.del_file().await;
+        if let Some(hook) = self.task.after.as_ref()
+        {
+            let status = match result.as_ref()
+            {
+                Ok(r) if r.has_warnings() => "warning",
+                Ok(_) => "ok",
+                Err(_) => "error"
+            };
+            if let Err(e) = hook.run(self.get_path(), task_id, Some(status)).await
+            {
+                let msg = ["Ошибка команды after для `", str_path, "`: ", &e].concat();
+                logger::error!("{}", &msg);
+                self.print_line(&msg);
+                return result.map(|mut r|
+                {
+                    r.failures.push((self.get_path().to_path_buf(), msg));
+                    r
+                });
+            }
+        }
+        result
+    }
+
+    pub async fn del_file(&self) -> Result<Report, String>
     {
-        let path = self.get_path();
         let str_path = self.get_str_path();
         if !self.path_is_exists()
         {
             return Err(["Файл `", str_path, "` не найден"].concat());
         }
-        let metadata = tokio::fs::metadata(path).await;
-        if let Ok(md) = metadata
+        self.guard.check(self.get_path())?;
+        let Selection { targets, mut skipped } = self.get_targets().await?;
+        let targets = self.verify_targets(targets, &mut skipped).await?;
+        let targets = if self.task.skip_open_files
         {
-            if md.is_file()
+            self.skip_open_files(targets, &mut skipped).await
+        }
+        else 
+        {
+            targets
+        };
+        for (p, reason) in &skipped
+        {
+            let msg = ["⏭ `", &p.to_string_lossy(), "` пропущен: ", reason].concat();
+            logger::warn!("{}", &msg);
+            self.print_line(msg);
+        }
+        if self.is_dry_run()
+        {
+            self.report_dry_run(&targets);
+            if self.is_selection()
+            {
+                let removed: HashSet<PathBuf> = targets.into_iter().map(|t| t.path).collect();
+                for d in self.prune_empty_dirs(&removed, true)
+                {
+                    let msg = ["[dry-run] ", &d.to_string_lossy(), " (пустая директория)"].concat();
+                    logger::info!("{}", &msg);
+                    self.print_line(msg);
+                }
+            }
+            return Ok(Report::default());
+        }
+        //nothing is selected, an empty archive isn't created
+        if let Some(settings) = self.task.archive.as_ref().filter(|_| !targets.is_empty())
+        {
+            match archive::pack(settings, self.get_path(), &targets).await
             {
-                let del = tokio::fs::remove_file(path).await;
-                return if del.is_ok()
+                Ok(a) => logger::info!("`{}` упакован в архив `{}`", str_path, a.display()),
+                Err(e) => return Err(["Ошибка архивации `", str_path, "`, удаление отменено: ", &e.to_string()].concat())
+            }
+        }
+        let shred_progress = self.task.shred.as_ref().filter(|_| self.task.mode == Mode::Delete).map(|s| self.begin_shred_progress(&targets, s));
+        let result = self.remove_targets(targets).await.map(|r| Report { skipped, ..r });
+        if let Some(state) = shred_progress
+        {
+            self.end_bytes_progress(state);
+        }
+        if let Ok(report) = result.as_ref()
+        {
+            if self.is_selection()
+            {
+                let mut status = report.summary(self.task.mode.past_tense());
+                let pruned = self.prune_empty_dirs(&HashSet::new(), false);
+                if !pruned.is_empty()
+                {
+                    logger::info!("`{}`: удалены пустые директории {:?}", str_path, &pruned);
+                    status.push_str(&[", пустых директорий ", &pruned.len().to_string()].concat());
+                }
+                if self.has_quota()
                 {
-                    Ok(()) 
+                    status.push_str(&[", освобождено ", &HumanBytes(report.bytes).to_string()].concat());
+                }
+                logger::info!("`{}`: {}", str_path, &status);
+                self.set_status(&status);
+            }
+        }
+        result
+    }
+
+    async fn remove_targets(&self, targets: Vec<Target>) -> Result<Report, String>
+    {
+        let str_path = self.get_str_path();
+        let mut report = Report::default();
+        if self.is_selection()
+        {
+            for t in targets
+            {
+                if let Err(e) = self.remove_audited(&t).await
+                {
+                    let reason = error_reason(&e);
+                    logger::error!("`{}`: {}", t.get_str_path(), &reason);
+                    self.print_line(["❌ `", t.get_str_path(), "`: ", &reason].concat());
+                    report.failed(t, reason);
                 }
                 else 
                 {
-                    match del.err().unwrap().kind()
+                    report.processed(t);
+                }
+            }
+            return Ok(report);
+        }
+        for t in targets
+        {
+            if let Err(e) = self.remove_audited(&t).await
+            {
+                match e.kind()
+                {
+                    tokio::io::ErrorKind::PermissionDenied | tokio::io::ErrorKind::ResourceBusy =>
+                        return Err(["Нет прав или файл `", str_path, "` занят другим приложением"].concat()),
+                    tokio::io::ErrorKind::NotFound =>
+                        return Err(["Файл `", str_path, "` не найден"].concat()),
+                    _=> report.failed(t, error_reason(&e))
+                }
+            }
+            else 
+            {
+                report.processed(t);
+            }
+        }
+        Ok(report)
+    }
+
+    ///process the target and write the result into the audit log, every file inside of the directory is written separately
+    async fn remove_audited(&self, target: &Target) -> std::io::Result<()>
+    {
+        if !audit::is_enabled()
+        {
+            return self.remove_target(target).await;
+        }
+        let mode = if self.task.mode == Mode::Delete && self.task.shred.is_some()
+        {
+            "shred"
+        }
+        else 
+        {
+            self.task.mode.name()
+        };
+        let files = if target.is_dir
+        {
+            targets::expand(std::slice::from_ref(target)).into_iter().filter(|e| !e.is_dir).collect()
+        }
+        else 
+        {
+            Vec::new()
+        };
+        //empty directory is written itself
+        let files = if files.is_empty() { vec![target.clone()] } else { files };
+        let task_id = self.task.get_hash();
+        let mut entries = Vec::with_capacity(files.len());
+        for f in &files
+        {
+            entries.push(AuditEntry::new(f, &task_id, mode, audit::with_digest()).await);
+        }
+        let result = self.remove_target(target).await;
+        for entry in entries
+        {
+            let entry = match result.as_ref()
+            {
+                Ok(_) => entry,
+                //the directory can be removed partially
+                Err(_) if target.is_dir && self.task.mode == Mode::Delete && tokio::fs::symlink_metadata(&entry.path).await.is_err() => entry,
+                Err(e) => entry.failed(&error_reason(e))
+            };
+            audit::record(&entry).await;
+        }
+        result
+    }
+
+    async fn remove_target(&self, target: &Target) -> std::io::Result<()>
+    {
+        if let Some(settings) = self.task.retry.as_ref()
+        {
+            retry::with_retry(settings, || self.process_target(target), |n, attempts|
+            {
+                let status = ["повтор ", &n.to_string(), "/", &attempts.to_string(), " `", target.get_str_path(), "`"].concat();
+                logger::warn!("{}", &status);
+                self.set_status(&status);
+            }).await
+        }
+        else 
+        {
+            self.process_target(target).await
+        }
+    }
+
+    async fn process_target(&self, target: &Target) -> std::io::Result<()>
+    {
+        if self.task.mode == Mode::Rotate
+        {
+            let settings = self.task.rotate.clone().unwrap_or_default();
+            let files = if target.is_dir
+            {
+                targets::expand(std::slice::from_ref(target)).into_iter().filter(|e| !e.is_dir && !e.is_link && !rotate::is_generation(&e.path)).collect()
+            }
+            else 
+            {
+                vec![target.clone()]
+            };
+            for f in files
+            {
+                if f.is_link
+                {
+                    return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "символическая ссылка не может быть ротирована"));
+                }
+                let rotated = rotate::rotate_file(&f.path, &settings).await?;
+                logger::info!("`{}` ротирован -> `{}`", f.get_str_path(), rotated.display());
+            }
+            return Ok(());
+        }
+        if self.task.mode == Mode::Truncate
+        {
+            let files = if target.is_dir
+            {
+                targets::expand(std::slice::from_ref(target)).into_iter().filter(|e| !e.is_dir && !e.is_link).collect()
+            }
+            else 
+            {
+                vec![target.clone()]
+            };
+            for f in files
+            {
+                if f.is_link
+                {
+                    return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "символическая ссылка не может быть усечена"));
+                }
+                let freed = truncate::truncate_file(&f.path, self.task.keep_bytes, self.task.keep_lines).await?;
+                logger::info!("`{}` усечен, освобождено {}", f.get_str_path(), HumanBytes(freed));
+            }
+            return Ok(());
+        }
+        if self.task.mode == Mode::Trash
+        {
+            let trashed = trash::move_to_trash(&target.path).await?;
+            logger::info!("`{}` перемещен в корзину -> `{}`", target.get_str_path(), trashed.display());
+            return Ok(());
+        }
+        if target.is_dir
+        {
+            if let Some(settings) = self.task.shred.as_ref()
+            {
+                //files are renamed inside of the directory by `rename`, so the new paths aren't needed
+                for e in targets::expand(std::slice::from_ref(target)).iter().filter(|e| !e.is_dir && !e.is_link)
+                {
+                    shred::shred_file(&e.path, settings, |n| self.pb.inc(n)).await?;
+                }
+            }
+            tokio::fs::remove_dir_all(&target.path).await
+        }
+        else if let Some(settings) = self.task.shred.as_ref().filter(|_| !target.is_link)
+        {
+            let path = shred::shred_file(&target.path, settings, |n| self.pb.inc(n)).await?;
+            tokio::fs::remove_file(&path).await
+        }
+        else 
+        {
+            tokio::fs::remove_file(&target.path).await
+        }
+    }
+
+    ///switch progressbar to the overwritten bytes counter
+    fn begin_shred_progress(&self, targets: &[Target], settings: &Shred) -> (Option<u64>, u64, String)
+    {
+        let total: u64 = targets::expand(targets).iter().filter(|e| !e.is_dir && !e.is_link).map(|e| settings.bytes_for(e.size)).sum();
+        let state = (self.pb.length(), self.pb.position(), self.pb.message());
+        self.pb.set_length(total);
+        self.pb.set_position(0);
+        self.pb.set_message(["перезапись `", self.get_str_path(), "` ", &HumanBytes(total).to_string()].concat());
+        state
+    }
+    ///restore progressbar state saved by `begin_shred_progress`
+    fn end_bytes_progress(&self, state: (Option<u64>, u64, String))
+    {
+        let (len, pos, msg) = state;
+        if let Some(len) = len
+        {
+            self.pb.set_length(len);
+        }
+        self.pb.set_position(pos);
+        self.pb.set_message(msg);
+    }
+
+    ///files and directories which will be removed by the task
+    async fn get_targets(&self) -> Result<Selection, String>
+    {
+        let path = self.get_path();
+        let is_link = tokio::fs::symlink_metadata(path).await.is_ok_and(|m| m.is_symlink());
+        if is_link
+        {
+            match self.task.symlinks
+            {
+                SymlinkPolicy::DeleteLink if !self.is_selection() => return Ok(vec![Target::link(path)].into()),
+                //the file or directory is processed by its real path, otherwise only the link would be removed
+                SymlinkPolicy::Follow =>
+                {
+                    let target = filters::follow_link(path).map_err(|e| ["`", self.get_str_path(), "`: ", e].concat())?;
+                    self.guard.check(&target.path)?;
+                    if !self.is_selection()
                     {
-                        tokio::io::ErrorKind::PermissionDenied | tokio::io::ErrorKind::ResourceBusy =>
-                            Err(["Нет прав или файл `", str_path, "` занят другим приложением"].concat()),
-                        tokio::io::ErrorKind::NotFound =>
-                            Err(["Файл `", str_path, "` не найден"].concat()),
-                        _=> Ok(())
+                        return Ok(vec![target].into());
                     }
+                },
+                _ =>
+                {
+                    let mut selection = Selection::default();
+                    selection.skip(path, "путь задачи является символической ссылкой");
+                    return Ok(selection);
+                }
+            }
+        }
+        let metadata = tokio::fs::metadata(path).await;
+        if let Ok(md) = metadata
+        {
+            if md.is_file()
+            {
+                return if self.age_matches(path)
+                {
+                    Ok(vec![Target::file(path, md.len())].into())
+                }
+                else
+                {
+                    Ok(Selection::default())
                 };
             }
             if md.is_dir()
             {
-                if let Some(mask ) = self.task.mask.as_ref()
+                return if self.has_quota()
+                {
+                    let usage: Vec<Target> = targets::expand(&[Target::dir(path)]).into_iter().filter(|t| !t.is_dir).collect();
+                    let mut selection = self.selected_files().await?;
+                    selection.targets = filters::over_quota(&usage, selection.targets, self.task.max_size, self.task.max_files);
+                    Ok(selection)
+                }
+                else if self.is_selection()
+                {
+                    self.selected_files().await
+                }
+                else 
+                {
+                    Ok(vec![Target::dir(path)].into())
+                };
+            }
+        }
+        Err("Ошибка получения метадаты".to_owned())
+    }
+
+    ///files inside of the task directory selected by mask, retention, age filter and symlink policy
+    async fn selected_files(&self) -> Result<Selection, String>
+    {
+        let path = self.get_path();
+        let str_path = self.get_str_path();
+        let files: Vec<Target> = if self.has_globs()
+        {
+            let globs = Globs::new(&self.task.include, &self.task.exclude, self.task.mask.as_ref())
+                .map_err(|e| ["Ошибка шаблона задачи `", str_path, "`: ", &e.to_string()].concat())?;
+            let regex = self.task.regex.as_ref()
+                .map(|r| regex::Regex::new(r))
+                .transpose()
+                .map_err(|e| ["Ошибка регулярного выражения задачи `", str_path, "`: ", &e.to_string()].concat())?;
+            targets::expand(&[Target::dir(path)])
+                .into_iter()
+                .filter(|t| 
+                {
+                    let relative = t.path.strip_prefix(path).unwrap_or(t.path.as_path());
+                    !t.is_dir 
+                    && globs.is_match(relative)
+                    && regex.as_ref().is_none_or(|r| filters::regex_match(r, relative, self.task.regex_on))
+                })
+                .collect()
+        }
+        else if let Some(mask ) = self.task.mask.as_ref()
+        {
+            if let Ok(files) = utilites::io::get_files_by_mask(path, mask).await
+            {
+                let mut targets = Vec::with_capacity(files.len());
+                for f in files
                 {
-                    return if let Ok(files) = utilites::io::get_files_by_mask(path, mask).await
+                    let f = PathBuf::from(f);
+                    if let Ok(md) = tokio::fs::symlink_metadata(&f).await
                     {
-                        for f in files
+                        if md.is_symlink()
                         {
-                            let _ = tokio::fs::remove_file(&f).await;
+                            targets.push(Target::link(f));
+                        }
+                        else 
+                        {
+                            targets.push(Target::file(f, md.len()));
                         }
-                        Ok(())
-                    }
-                    else 
-                    {
-                        Err(["При операции c `", str_path, "` произошла ошибка"].concat())
                     }
                 }
-                else 
+                targets
+            }
+            else 
+            {
+                return Err(["При операции c `", str_path, "` произошла ошибка"].concat());
+            }
+        }
+        else 
+        {
+            targets::expand(&[Target::dir(path)])
+                .into_iter()
+                .filter(|t| !t.is_dir)
+                .collect()
+        };
+        let files = if let Some(n) = self.task.keep_last
+        {
+            filters::keep_last(files, n, self.task.keep_by)
+        }
+        else 
+        {
+            files
+        };
+        let mut selection = Selection::default();
+        let files = files.into_iter().filter(|t| self.task.mode != Mode::Rotate || !rotate::is_generation(&t.path));
+        for f in files.filter(|t| self.age_matches(&t.path))
+        {
+            let link = f.path.clone();
+            match filters::apply_symlink_policy(f, path, self.task.symlinks)
+            {
+                Ok(t) => selection.targets.push(t),
+                Err(reason) => selection.skip(link, reason)
+            }
+        }
+        Ok(selection)
+    }
+
+    ///move targets opened by any process into skipped, directory is skipped if any file inside is opened
+    async fn skip_open_files(&self, targets: Vec<Target>, skipped: &mut Vec<(PathBuf, String)>) -> Vec<Target>
+    {
+        let open = tokio::task::spawn_blocking(OpenFiles::scan).await.unwrap_or_default();
+        let mut closed = Vec::with_capacity(targets.len());
+        for t in targets
+        {
+            let is_open = if t.is_dir
+            {
+                targets::expand(std::slice::from_ref(&t)).iter().any(|e| open.is_open(&e.path))
+            }
+            else 
+            {
+                open.is_open(&t.path)
+            };
+            if is_open
+            {
+                skipped.push((t.path, "файл открыт другим процессом".to_owned()));
+            }
+            else 
+            {
+                closed.push(t);
+            }
+        }
+        closed
+    }
+
+    ///check BLAKE3 digests of the targets, mismatched files of the selection are skipped,
+    ///for a single file or directory the task fails
+    async fn verify_targets(&self, targets: Vec<Target>, skipped: &mut Vec<(PathBuf, String)>) -> Result<Vec<Target>, String>
+    {
+        if self.task.blake3.is_none() && self.task.blake3_manifest.is_none()
+        {
+            return Ok(targets);
+        }
+        let str_path = self.get_str_path();
+        let root = self.get_path();
+        let manifest = if let Some(m) = self.task.blake3_manifest.as_ref()
+        {
+            Some(verify::load_manifest(m).await.map_err(|e| ["Ошибка чтения манифеста `", &m.to_string_lossy(), "`: ", &e.to_string()].concat())?)
+        }
+        else 
+        {
+            None
+        };
+        //digest for the file from the `blake3` field or from the manifest
+        let expected = |path: &Path| -> Option<String>
+        {
+            if let Some(d) = self.task.blake3.as_ref()
+            {
+                return Some(d.clone());
+            }
+            let key = if path == root
+            {
+                PathBuf::from(path.file_name().unwrap_or_default())
+            }
+            else 
+            {
+                verify::manifest_key(root, path)
+            };
+            manifest.as_ref().and_then(|m| m.get(&key).cloned())
+        };
+        if self.is_selection()
+        {
+            let mut verified = Vec::with_capacity(targets.len());
+            for t in targets
+            {
+                let result = match expected(&t.path)
                 {
-                    let del = tokio::fs::remove_dir_all(path).await;
-                    return if del.is_ok()
+                    Some(d) => verify::check(&t.path, &d).await,
+                    None => Err("файл отсутствует в манифесте".to_owned())
+                };
+                match result
+                {
+                    Ok(_) => verified.push(t),
+                    Err(reason) => skipped.push((t.path, reason))
+                }
+            }
+            return Ok(verified);
+        }
+        for t in &targets
+        {
+            if t.is_dir
+            {
+                let Some(manifest) = manifest.as_ref() else
+                {
+                    return Err(["Для проверки директории `", str_path, "` необходим `blake3_manifest`, удаление отменено"].concat());
+                };
+                let files: Vec<Target> = targets::expand(std::slice::from_ref(t)).into_iter().filter(|e| !e.is_dir).collect();
+                for (key, _) in manifest
+                {
+                    if !files.iter().any(|f| &verify::manifest_key(root, &f.path) == key)
                     {
-                        Ok(())   
+                        return Err(["Файл `", &key.to_string_lossy(), "` из манифеста отсутствует в `", str_path, "`, удаление отменено"].concat());
                     }
-                    else 
+                }
+                for f in &files
+                {
+                    let result = match expected(&f.path)
                     {
-                        match del.err().unwrap().kind()
-                        {
-                            tokio::io::ErrorKind::PermissionDenied | tokio::io::ErrorKind::ResourceBusy =>
-                                Err(["Нет прав или файл `", str_path, "` занят другим приложением"].concat()),
-                            tokio::io::ErrorKind::NotFound =>
-                                Err(["Файл `", str_path, "` не найден"].concat()),
-                            _=> Ok(())
-                        }
+                        Some(d) => verify::check(&f.path, &d).await,
+                        None => Err("файл отсутствует в манифесте".to_owned())
                     };
+                    if let Err(reason) = result
+                    {
+                        return Err(["Файл `", f.get_str_path(), "`: ", &reason, ", удаление отменено"].concat());
+                    }
+                }
+            }
+            else 
+            {
+                let result = match expected(&t.path)
+                {
+                    Some(d) => verify::check(&t.path, &d).await,
+                    None => Err("файл отсутствует в манифесте".to_owned())
+                };
+                if let Err(reason) = result
+                {
+                    return Err(["Файл `", t.get_str_path(), "`: ", &reason, ", удаление отменено"].concat());
                 }
             }
         }
-        Err("Ошибка получения метадаты".to_owned())
-        
+        Ok(targets)
+    }
+
+    ///remove empty directories after processing of the selected files if `prune_empty_dirs` is set
+    fn prune_empty_dirs(&self, removed: &HashSet<PathBuf>, dry_run: bool) -> Vec<PathBuf>
+    {
+        if !self.task.prune_empty_dirs
+        {
+            return Vec::new();
+        }
+        let root = self.get_path();
+        let globs = Globs::new(&[], &self.task.exclude, None).ok();
+        let excluded = |dir: &Path| 
+        {
+            let relative = dir.strip_prefix(root).unwrap_or(dir);
+            globs.as_ref().is_some_and(|g| g.is_dir_excluded(relative))
+        };
+        targets::prune_empty_dirs(root, self.task.prune_root, removed, dry_run, &excluded)
+    }
+
+    ///files are selected by walking the directory tree with include/exclude globs or regex
+    fn has_globs(&self) -> bool
+    {
+        !self.task.include.is_empty() || !self.task.exclude.is_empty() || self.task.regex.is_some()
+    }
+    fn has_quota(&self) -> bool
+    {
+        self.task.max_size.is_some() || self.task.max_files.is_some()
+    }
+    fn has_age_filter(&self) -> bool
+    {
+        self.task.older_than.is_some() || self.task.newer_than.is_some()
+    }
+    fn age_matches(&self, path: &Path) -> bool
+    {
+        !self.has_age_filter() || filters::is_aged(path, self.task.age_by, self.task.older_than, self.task.newer_than)
+    }
+    ///task processes separate files selected inside the directory instead of the whole directory
+    fn is_selection(&self) -> bool
+    {
+        self.task.mask.is_some() || self.has_globs() || self.has_age_filter() || self.task.symlinks == SymlinkPolicy::Skip || self.has_quota() || self.task.keep_last.is_some()
+    }
+
+    ///append status to the task message
+    fn set_status(&self, status: &str)
+    {
+        if self.task.when_free_below.is_some()
+        {
+            self.update_free_space();
+        }
+        else if let Some(d) = self.task.date.as_ref()
+        {
+            Self::set_date_message(&self.pb, self.task.visible, d, self.get_path(), self.task.mask.as_ref(), &self.get_strategy());
+        }
+        else 
+        {
+            Self::set_interval_message(&self.pb, self.task.visible, self.get_path(), self.task.mask.as_ref(), &self.get_strategy());
+        }
+        self.pb.set_message([&self.pb.message(), " [", status, "]"].concat());
+    }
+
+    ///print and log the exact list of entries which would be removed by the task
+    fn report_dry_run(&self, targets: &[Target])
+    {
+        let entries = targets::expand(targets);
+        let mut files = 0;
+        let mut dirs = 0;
+        let mut bytes = 0;
+        for e in &entries
+        {
+            let msg = if e.is_dir
+            {
+                dirs += 1;
+                ["[dry-run] ", e.get_str_path()].concat()
+            }
+            else 
+            {
+                files += 1;
+                bytes += e.size;
+                if e.is_link
+                {
+                    ["[dry-run] ", e.get_str_path(), " (символическая ссылка)"].concat()
+                }
+                else 
+                {
+                    ["[dry-run] ", e.get_str_path(), " (", &HumanBytes(e.size).to_string(), ")"].concat()
+                }
+            };
+            logger::info!("{}", &msg);
+            self.print_line(msg);
+        }
+        let msg = ["[dry-run] `", self.get_str_path(), "`: будет ", self.task.mode.past_tense(), " файлов: ", &files.to_string(), 
+        ", директорий: ", &dirs.to_string(), ", всего ", &HumanBytes(bytes).to_string()].concat();
+        logger::info!("{}", &msg);
+        self.print_line(msg);
     }
 
     fn set_date_message<P: AsRef<Path>>(pb: &ProgressBar, visible: bool, date: &Date, path: P, mask: Option<&String>, strategy: &RepeatingStrategy)
@@ -332,6 +1420,40 @@ impl TaskWithProgress
         }
     }
 
+    ///`свободно 4.2 GiB из 100 GiB, порог 5GiB`, used space fills the bar
+    fn set_free_space_message<P: AsRef<Path>>(pb: &ProgressBar, visible: bool, threshold: &Threshold, path: P, mask: Option<&String>) -> Option<Usage>
+    {
+        let path = path.as_ref();
+        let (usage, free) = match freespace::usage(path)
+        {
+            Ok(u) =>
+            {
+                pb.set_length(u.total);
+                pb.set_position(u.total.saturating_sub(u.free));
+                (Some(u), ["свободно ", &HumanBytes(u.free).to_string(), " из ", &HumanBytes(u.total).to_string(), ", порог ", &threshold.to_string()].concat())
+            },
+            Err(e) => (None, ["ошибка определения свободного места: ", &e.to_string()].concat())
+        };
+        let path = path.as_os_str().to_str().unwrap_or_default();
+        let msg= if visible
+        {
+            if let Some(m) = mask
+            {
+                [&free, " -> ", path, " (", m, ")"].concat()
+            }
+            else
+            {
+                [&free, " -> ", path].concat()
+            }
+        }
+        else
+        {
+            free
+        };
+        pb.set_message(msg);
+        usage
+    }
+
     fn set_interval_message<P: AsRef<Path>>(pb: &ProgressBar, visible: bool, path: P, mask: Option<&String>, strategy: &RepeatingStrategy)
     {
         let path = path.as_ref();
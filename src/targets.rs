@@ -0,0 +1,231 @@
+use std::{collections::HashSet, path::{Path, PathBuf}};
+
+///file or directory selected by the task for processing
+#[derive(Debug, Clone)]
+pub struct Target
+{
+    pub path: PathBuf,
+    pub size: u64,
+    pub is_dir: bool,
+    pub is_link: bool
+}
+
+impl Target
+{
+    pub fn file<P: AsRef<Path>>(path: P, size: u64) -> Self
+    {
+        Self
+        {
+            path: path.as_ref().to_path_buf(),
+            size,
+            is_dir: false,
+            is_link: false
+        }
+    }
+    pub fn link<P: AsRef<Path>>(path: P) -> Self
+    {
+        Self
+        {
+            path: path.as_ref().to_path_buf(),
+            size: 0,
+            is_dir: false,
+            is_link: true
+        }
+    }
+    pub fn dir<P: AsRef<Path>>(path: P) -> Self
+    {
+        Self
+        {
+            path: path.as_ref().to_path_buf(),
+            size: 0,
+            is_dir: true,
+            is_link: false
+        }
+    }
+    pub fn get_str_path(&self) -> &str
+    {
+        self.path.as_os_str().to_str().unwrap_or_default()
+    }
+}
+
+///entries selected by the task and entries skipped with the reason
+#[derive(Debug, Default)]
+pub struct Selection
+{
+    pub targets: Vec<Target>,
+    pub skipped: Vec<(PathBuf, String)>
+}
+impl From<Vec<Target>> for Selection
+{
+    fn from(targets: Vec<Target>) -> Self
+    {
+        Self
+        {
+            targets,
+            skipped: Vec::new()
+        }
+    }
+}
+impl Selection
+{
+    pub fn skip<P: AsRef<Path>>(&mut self, path: P, reason: &str)
+    {
+        self.skipped.push((path.as_ref().to_path_buf(), reason.to_owned()));
+    }
+}
+
+///result of the task processing
+#[derive(Debug, Default)]
+pub struct Report
+{
+    ///count of successfully processed entries
+    pub count: usize,
+    ///size of successfully processed files
+    pub bytes: u64,
+    ///failed entries with the reason
+    pub failures: Vec<(PathBuf, String)>,
+    ///entries skipped by the task settings with the reason
+    pub skipped: Vec<(PathBuf, String)>
+}
+impl Report
+{
+    pub fn processed(&mut self, target: Target)
+    {
+        self.count += 1;
+        self.bytes += target.size;
+    }
+    pub fn failed(&mut self, target: Target, reason: String)
+    {
+        self.failures.push((target.path, reason));
+    }
+    pub fn has_failures(&self) -> bool
+    {
+        !self.failures.is_empty()
+    }
+    ///some entries were failed or skipped
+    pub fn has_warnings(&self) -> bool
+    {
+        self.has_failures() || !self.skipped.is_empty()
+    }
+    ///`удалено 12, ошибок 3, пропущено 1`
+    pub fn summary(&self, action: &str) -> String
+    {
+        let mut summary = [action, " ", &self.count.to_string()].concat();
+        if self.has_failures()
+        {
+            summary.push_str(&[", ошибок ", &self.failures.len().to_string()].concat());
+        }
+        if !self.skipped.is_empty()
+        {
+            summary.push_str(&[", пропущено ", &self.skipped.len().to_string()].concat());
+        }
+        summary
+    }
+}
+
+///human readable reason of io error
+pub fn error_reason(error: &std::io::Error) -> String
+{
+    match error.kind()
+    {
+        std::io::ErrorKind::PermissionDenied | std::io::ErrorKind::ResourceBusy => "нет прав или файл занят другим приложением".to_owned(),
+        std::io::ErrorKind::NotFound => "файл не найден".to_owned(),
+        _ => error.to_string()
+    }
+}
+
+///expand directories into the full list of nested entries (directory itself goes last)
+///symlinks are not followed
+pub fn expand(targets: &[Target]) -> Vec<Target>
+{
+    let mut entries = Vec::new();
+    for t in targets
+    {
+        if t.is_dir
+        {
+            walk(&t.path, &mut entries);
+        }
+        else
+        {
+            entries.push(t.clone());
+        }
+    }
+    entries
+}
+
+fn walk(path: &Path, entries: &mut Vec<Target>)
+{
+    if let Ok(dir) = std::fs::read_dir(path)
+    {
+        for entry in dir.flatten()
+        {
+            let entry_path = entry.path();
+            if let Ok(md) = std::fs::symlink_metadata(&entry_path)
+            {
+                if md.is_symlink()
+                {
+                    entries.push(Target::link(&entry_path));
+                }
+                else if md.is_dir()
+                {
+                    walk(&entry_path, entries);
+                }
+                else
+                {
+                    entries.push(Target::file(&entry_path, md.len()));
+                }
+            }
+        }
+    }
+    entries.push(Target::dir(path));
+}
+
+///remove directories left empty beneath `root` (the root itself only with `with_root`), returns removed directories
+///entries from `removed` are counted as already removed, with `dry_run` nothing is removed from disk
+///symlinks are never traversed and keep their directory non-empty
+pub fn prune_empty_dirs(root: &Path, with_root: bool, removed: &HashSet<PathBuf>, dry_run: bool, excluded: &dyn Fn(&Path) -> bool) -> Vec<PathBuf>
+{
+    let mut pruned = Vec::new();
+    let empty = prune(root, removed, dry_run, excluded, &mut pruned);
+    if empty && with_root && (dry_run || std::fs::remove_dir(root).is_ok())
+    {
+        pruned.push(root.to_path_buf());
+    }
+    pruned
+}
+
+///returns true if directory is empty after pruning
+fn prune(dir: &Path, removed: &HashSet<PathBuf>, dry_run: bool, excluded: &dyn Fn(&Path) -> bool, pruned: &mut Vec<PathBuf>) -> bool
+{
+    let Ok(entries) = std::fs::read_dir(dir) else
+    {
+        return false;
+    };
+    let mut empty = true;
+    for entry in entries.flatten()
+    {
+        let path = entry.path();
+        if removed.contains(&path)
+        {
+            continue;
+        }
+        match std::fs::symlink_metadata(&path)
+        {
+            Ok(md) if md.is_dir() =>
+            {
+                if prune(&path, removed, dry_run, excluded, pruned)
+                && !excluded(&path)
+                && (dry_run || std::fs::remove_dir(&path).is_ok())
+                {
+                    pruned.push(path);
+                }
+                else
+                {
+                    empty = false;
+                }
+            },
+            _ => empty = false
+        }
+    }
+    empty
+}
@@ -1,7 +1,10 @@
-use std::{collections::HashMap, sync::Arc};
-use scheduler::{SchedulerEvent, SchedulerHandler};
-use tokio::sync::RwLock;
-use crate::structs::TaskWithProgress;
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use scheduler::{RepeatingStrategy, SchedulerEvent, SchedulerHandler};
+use tokio::{sync::RwLock, time::Instant};
+use crate::{freespace::Trigger, structs::TaskWithProgress};
+
+///how often free space of the tasks with `when_free_below` is checked
+const FREE_SPACE_POLL: Duration = Duration::from_secs(30);
 
 
 pub struct Handler
@@ -47,31 +50,172 @@ impl SchedulerHandler<Arc<String>> for Handler
                 },
                 SchedulerEvent::Finish(event) =>
                 {
-                    let guard = task.read().await;
                     logger::debug!("finish event_id: {:?}", &event);
-                    if let Some(t) = guard.get(&event)
+                    let t = task.read().await.get(&event).cloned();
+                    if let Some(t) = t
                     {
-                        if let Err(e) = t.del_file().await
+                        match t.run(&event).await
                         {
-                            t.finish_with_err(e);
-                        }
-                        else 
-                        {
-                            t.finish();
+                            Ok(report) if report.has_warnings() => t.finish_with_warning(),
+                            Ok(_) => t.finish(),
+                            Err(e) => t.finish_with_err(e)
                         }
                     }
                 },
                 SchedulerEvent::FinishCycle(event) =>
                 {
                     logger::debug!("finish_cycle event_id: {:?}", &event);
-                    let mut guard = task.write().await;
-                    if let Some(t) = guard.get_mut(&event.id)
+                    //the lock isn't held while the task runs, retries with backoff can take minutes
+                    let t = 
+                    {
+                        let mut guard = task.write().await;
+                        guard.get_mut(&event.id).map(|t|
+                        {
+                            t.update_progress_with_cycle(event.current as u64, event.len as u64);
+                            t.clone()
+                        })
+                    };
+                    if let Some(t) = t
                     {
-                        t.update_progress_with_cycle(event.current as u64, event.len as u64);
-                        let _ = t.del_file().await;
+                        t.finish_cycle(t.run(&event.id).await);
                     }
                 }
             };
         }
     }
-}
\ No newline at end of file
+}
+///poll free space of the task filesystem and run the task when it drops below the threshold,
+///`once` tasks are finished after the first run
+pub fn watch_free_space(id: Arc<String>, tasks: Arc<RwLock<HashMap<Arc<String>, TaskWithProgress>>>)
+{
+    tokio::spawn(async move
+    {
+        let mut trigger: Option<Trigger> = None;
+        loop
+        {
+            //the lock isn't held while the task runs, processing can take minutes
+            let fired = 
+            {
+                let guard = tasks.read().await;
+                let Some(t) = guard.get(&id) else
+                {
+                    break;
+                };
+                let Some(threshold) = t.get_free_space_threshold() else
+                {
+                    break;
+                };
+                let trigger = trigger.get_or_insert_with(|| Trigger::new(threshold));
+                match t.update_free_space()
+                {
+                    Some(usage) if trigger.check(&usage) =>
+                    {
+                        logger::info!("`{}`: свободного места меньше порога {}, запуск задачи", t.get_str_path(), threshold);
+                        Some(t.clone())
+                    },
+                    _ => None
+                }
+            };
+            if let Some(t) = fired
+            {
+                let result = t.run(&id).await;
+                if t.get_strategy() == RepeatingStrategy::Once
+                {
+                    match result
+                    {
+                        Ok(report) if report.has_warnings() => t.finish_with_warning(),
+                        Ok(_) => t.finish(),
+                        Err(e) => t.finish_with_err(e)
+                    }
+                    break;
+                }
+                t.finish_cycle(result);
+            }
+            tokio::time::sleep(FREE_SPACE_POLL).await;
+        }
+    });
+}
+
+///run the task at times matching its `cron` expression, calendar repeats or by interval with seconds,
+///`once` tasks are finished after the first run
+pub fn watch_schedule(id: Arc<String>, tasks: Arc<RwLock<HashMap<Arc<String>, TaskWithProgress>>>)
+{
+    tokio::spawn(async move
+    {
+        let mut len = 
+        {
+            let mut guard = tasks.write().await;
+            match guard.get_mut(&id).map(|t| (t.schedule_next(), t))
+            {
+                Some((Some(len), _)) => len,
+                Some((None, t)) =>
+                {
+                    t.finish_with_err(["Для `", t.get_str_path(), "` не найдено время следующего запуска"].concat());
+                    return;
+                },
+                None => return
+            }
+        };
+        let mut deadline = Instant::now() + Duration::from_secs(len);
+        loop
+        {
+            while let Some(left) = deadline.checked_duration_since(Instant::now()).filter(|l| !l.is_zero())
+            {
+                tokio::time::sleep(left.min(Duration::from_secs(1))).await;
+                let guard = tasks.read().await;
+                if let Some(t) = guard.get(&id)
+                {
+                    let left = deadline.saturating_duration_since(Instant::now()).as_secs();
+                    t.update_progress(len.saturating_sub(left), len);
+                }
+            }
+            //the next run is scheduled under the lock, the task runs on a copy without it,
+            //so other tasks and usb configs aren't blocked while processing takes minutes
+            let scheduled = Instant::now();
+            let (t, next) = 
+            {
+                let mut guard = tasks.write().await;
+                let Some(t) = guard.get_mut(&id) else
+                {
+                    break;
+                };
+                let next = if t.get_strategy() == RepeatingStrategy::Once
+                {
+                    None
+                }
+                else 
+                {
+                    //next run is shown before the status of this run, as for the cycles of scheduler date tasks
+                    Some(t.schedule_next())
+                };
+                (t.clone(), next)
+            };
+            let result = t.run(&id).await;
+            match next
+            {
+                None =>
+                {
+                    match result
+                    {
+                        Ok(report) if report.has_warnings() => t.finish_with_warning(),
+                        Ok(_) => t.finish(),
+                        Err(e) => t.finish_with_err(e)
+                    }
+                    break;
+                },
+                Some(Some(next)) =>
+                {
+                    t.finish_cycle(result);
+                    len = next;
+                    deadline = scheduled + Duration::from_secs(next);
+                },
+                Some(None) =>
+                {
+                    t.finish_cycle(result);
+                    t.finish_with_err(["Для `", t.get_str_path(), "` не найдено время следующего запуска"].concat());
+                    break;
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,184 @@
+//! moving files into trash by freedesktop.org Trash specification
+//! https://specifications.freedesktop.org/trash-spec/latest/
+use std::path::{Path, PathBuf};
+
+///move file or directory into the trash, returns the new path of the file
+pub async fn move_to_trash(path: &Path) -> std::io::Result<PathBuf>
+{
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || trash(&path))
+        .await
+        .map_err(std::io::Error::other)?
+}
+
+#[cfg(not(unix))]
+fn trash(_path: &Path) -> std::io::Result<PathBuf>
+{
+    Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "корзина поддерживается только в unix системах"))
+}
+
+#[cfg(unix)]
+fn trash(path: &Path) -> std::io::Result<PathBuf>
+{
+    use std::os::unix::fs::MetadataExt;
+    let path = std::path::absolute(path)?;
+    let md = std::fs::symlink_metadata(&path)?;
+    let (trash_dir, topdir) = trash_dir_for(&path, md.dev())?;
+    let files = trash_dir.join("files");
+    let info = trash_dir.join("info");
+    create_private_dir(&files)?;
+    create_private_dir(&info)?;
+    //inside of per-mountpoint trash original path is relative to the mountpoint
+    let original = if let Some(top) = topdir.as_ref()
+    {
+        path.strip_prefix(top).unwrap_or(path.as_path()).to_path_buf()
+    }
+    else
+    {
+        path.clone()
+    };
+    let name = path.file_name().ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, "некорректное имя файла"))?;
+    let (info_file, trashed) = reserve_name(&files, &info, name, &original)?;
+    if let Err(e) = std::fs::rename(&path, &trashed)
+    {
+        let _ = std::fs::remove_file(info_file);
+        return Err(e);
+    }
+    Ok(trashed)
+}
+
+///home trash `$XDG_DATA_HOME/Trash` (`~/.local/share/Trash` by default)
+#[cfg(unix)]
+fn home_trash() -> Option<PathBuf>
+{
+    std::env::var_os("XDG_DATA_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".local").join("share")))
+        .map(|d| d.join("Trash"))
+}
+
+///home trash if the file is on the same device, otherwise `$topdir/.Trash/$uid` or `$topdir/.Trash-$uid`
+#[cfg(unix)]
+fn trash_dir_for(path: &Path, dev: u64) -> std::io::Result<(PathBuf, Option<PathBuf>)>
+{
+    use std::os::unix::fs::PermissionsExt;
+    if let Some(home) = home_trash()
+    {
+        if device_of(&home) == Some(dev)
+        {
+            return Ok((home, None));
+        }
+    }
+    let top = mount_top(path, dev);
+    let uid = unsafe { libc::getuid() }.to_string();
+    let admin = top.join(".Trash");
+    if let Ok(md) = std::fs::symlink_metadata(&admin)
+    {
+        //admin created trash must be a real directory with sticky bit
+        if md.is_dir() && md.permissions().mode() & 0o1000 != 0
+        {
+            let dir = admin.join(&uid);
+            if create_private_dir(&dir).is_ok()
+            {
+                return Ok((dir, Some(top)));
+            }
+        }
+    }
+    Ok((top.join([".Trash-", &uid].concat()), Some(top)))
+}
+
+///device of the path or its nearest existing parent
+#[cfg(unix)]
+fn device_of(path: &Path) -> Option<u64>
+{
+    use std::os::unix::fs::MetadataExt;
+    path.ancestors().find_map(|p| std::fs::metadata(p).ok()).map(|m| m.dev())
+}
+
+///topmost directory on the same device (mountpoint)
+#[cfg(unix)]
+fn mount_top(path: &Path, dev: u64) -> PathBuf
+{
+    let mut top = path.parent().unwrap_or(path).to_path_buf();
+    while let Some(parent) = top.parent()
+    {
+        if device_of(parent) != Some(dev)
+        {
+            break;
+        }
+        top = parent.to_path_buf();
+    }
+    top
+}
+
+#[cfg(unix)]
+fn create_private_dir(path: &Path) -> std::io::Result<()>
+{
+    use std::os::unix::fs::DirBuilderExt;
+    std::fs::DirBuilder::new().recursive(true).mode(0o700).create(path)
+}
+
+///atomically create `.trashinfo` file with unique name, returns paths of info file and of the file inside trash
+#[cfg(unix)]
+pub fn reserve_name(files: &Path, info: &Path, name: &std::ffi::OsStr, original: &Path) -> std::io::Result<(PathBuf, PathBuf)>
+{
+    use std::io::Write;
+    let name = Path::new(name);
+    let stem = name.file_stem().unwrap_or(name.as_os_str()).to_string_lossy().to_string();
+    let ext = name.extension().map(|e| [".", &e.to_string_lossy()].concat()).unwrap_or_default();
+    let date = utilites::Date::now().as_naive_datetime().format("%Y-%m-%dT%H:%M:%S").to_string();
+    let mut n = 1;
+    loop
+    {
+        let candidate = if n == 1
+        {
+            name.as_os_str().to_os_string()
+        }
+        else
+        {
+            [stem.as_str(), ".", &n.to_string(), &ext].concat().into()
+        };
+        n += 1;
+        let trashed = files.join(&candidate);
+        let mut info_name = candidate.clone();
+        info_name.push(".trashinfo");
+        let info_file = info.join(info_name);
+        if std::fs::symlink_metadata(&trashed).is_ok()
+        {
+            continue;
+        }
+        match std::fs::OpenOptions::new().write(true).create_new(true).open(&info_file)
+        {
+            Ok(mut f) =>
+            {
+                let content = ["[Trash Info]\nPath=", &encode_path(original), "\nDeletionDate=", &date, "\n"].concat();
+                if let Err(e) = f.write_all(content.as_bytes())
+                {
+                    let _ = std::fs::remove_file(&info_file);
+                    return Err(e);
+                }
+                return Ok((info_file, trashed));
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e)
+        }
+    }
+}
+
+///percent-encoding of the path as required by the `Path` key of `.trashinfo`
+#[cfg(unix)]
+pub fn encode_path(path: &Path) -> String
+{
+    use std::os::unix::ffi::OsStrExt;
+    let mut encoded = String::new();
+    for b in path.as_os_str().as_bytes()
+    {
+        match *b
+        {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => encoded.push(*b as char),
+            _ => encoded.push_str(&format!("%{:02X}", b))
+        }
+    }
+    encoded
+}
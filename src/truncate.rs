@@ -0,0 +1,73 @@
+use std::{fs::File, io::{Read, Seek, SeekFrom, Write}, path::Path};
+
+pub const CHUNK_SIZE: u64 = 64 * 1024;
+
+///truncate file in place (the file stays opened by other programs), keeping the last `keep_bytes` bytes or `keep_lines` lines
+///returns count of freed bytes
+pub async fn truncate_file(path: &Path, keep_bytes: Option<u64>, keep_lines: Option<usize>) -> std::io::Result<u64>
+{
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || truncate(&path, keep_bytes, keep_lines))
+        .await
+        .map_err(std::io::Error::other)?
+}
+
+pub fn truncate(path: &Path, keep_bytes: Option<u64>, keep_lines: Option<usize>) -> std::io::Result<u64>
+{
+    let mut file = std::fs::OpenOptions::new().read(true).write(true).open(path)?;
+    let len = file.metadata()?.len();
+    let mut offset = len;
+    if let Some(lines) = keep_lines
+    {
+        offset = offset.min(lines_offset(&mut file, len, lines)?);
+    }
+    if let Some(bytes) = keep_bytes
+    {
+        offset = offset.min(len.saturating_sub(bytes));
+    }
+    let mut tail = Vec::with_capacity((len - offset) as usize);
+    if offset < len
+    {
+        file.seek(SeekFrom::Start(offset))?;
+        file.read_to_end(&mut tail)?;
+    }
+    file.set_len(0)?;
+    file.seek(SeekFrom::Start(0))?;
+    file.write_all(&tail)?;
+    file.sync_all()?;
+    Ok(offset)
+}
+
+///offset of the beginning of the last `lines` lines
+pub fn lines_offset(file: &mut File, len: u64, lines: usize) -> std::io::Result<u64>
+{
+    if lines == 0
+    {
+        return Ok(len);
+    }
+    let mut found = 0;
+    let mut end = len;
+    let mut buf = vec![0u8; CHUNK_SIZE as usize];
+    while end > 0
+    {
+        let start = end.saturating_sub(CHUNK_SIZE);
+        let chunk = &mut buf[..(end - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(chunk)?;
+        for (i, b) in chunk.iter().enumerate().rev()
+        {
+            let pos = start + i as u64;
+            //newline at the end of file doesn't start a new line
+            if *b == b'\n' && pos + 1 != len
+            {
+                found += 1;
+                if found == lines
+                {
+                    return Ok(pos + 1);
+                }
+            }
+        }
+        end = start;
+    }
+    Ok(0)
+}
@@ -0,0 +1,67 @@
+use std::{collections::HashMap, path::{Path, PathBuf}};
+
+///BLAKE3 digest of the file content in hex
+pub async fn hash_file(path: &Path) -> std::io::Result<String>
+{
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move ||
+    {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update_reader(std::fs::File::open(&path)?)?;
+        Ok(hasher.finalize().to_hex().to_string())
+    })
+    .await
+    .map_err(std::io::Error::other)?
+}
+
+pub fn is_valid_digest(digest: &str) -> bool
+{
+    digest.len() == 64 && digest.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+///manifest in `b3sum` format: `<digest>  <path relative to the task directory>` on each line
+pub async fn load_manifest(path: &Path) -> std::io::Result<HashMap<PathBuf, String>>
+{
+    let content = tokio::fs::read_to_string(path).await?;
+    let mut manifest = HashMap::new();
+    for (n, line) in content.lines().enumerate()
+    {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#')
+        {
+            continue;
+        }
+        let parsed = line.split_once(char::is_whitespace)
+            .map(|(digest, file)| (digest.to_ascii_lowercase(), file.trim().trim_start_matches('*')))
+            .filter(|(digest, file)| is_valid_digest(digest) && !file.is_empty());
+        if let Some((digest, file)) = parsed
+        {
+            manifest.insert(PathBuf::from(file.replace('\\', "/")), digest);
+        }
+        else
+        {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, ["некорректная строка ", &(n + 1).to_string(), " манифеста"].concat()));
+        }
+    }
+    Ok(manifest)
+}
+
+///key of the file in the manifest
+pub fn manifest_key(root: &Path, path: &Path) -> PathBuf
+{
+    PathBuf::from(path.strip_prefix(root).unwrap_or(path).to_string_lossy().replace('\\', "/"))
+}
+
+///check file digest, returns the reason of mismatch
+pub async fn check(path: &Path, expected: &str) -> Result<(), String>
+{
+    let actual = hash_file(path).await.map_err(|e| ["ошибка вычисления контрольной суммы: ", &e.to_string()].concat())?;
+    if actual.eq_ignore_ascii_case(expected)
+    {
+        Ok(())
+    }
+    else
+    {
+        Err(["контрольная сумма ", &actual, " не совпадает с ожидаемой ", expected].concat())
+    }
+}
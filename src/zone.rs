@@ -0,0 +1,101 @@
+use chrono::{DateTime, FixedOffset, Local, NaiveDateTime, TimeDelta, TimeZone};
+use chrono_tz::Tz;
+use serde::Serializer;
+use utilites::Date;
+
+///date from the config: local time of the task zone or RFC 3339 with offset `2025-02-15T21:33:44+03:00`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ZonedDate
+{
+    naive: NaiveDateTime,
+    offset: Option<FixedOffset>
+}
+
+impl ZonedDate
+{
+    pub fn parse(value: &str) -> Option<Self>
+    {
+        if let Ok(d) = DateTime::parse_from_rfc3339(value.trim())
+        {
+            return Some(Self { naive: d.naive_local(), offset: Some(*d.offset()) });
+        }
+        Date::parse(value).map(|d| d.into())
+    }
+    ///the same moment in the local time of this computer,
+    ///date without offset is read in `zone` or in the local zone
+    pub fn to_local(&self, zone: Option<Tz>) -> Option<Date>
+    {
+        let local = match (self.offset, zone)
+        {
+            (Some(offset), _) => offset.from_local_datetime(&self.naive).single()?.with_timezone(&Local).naive_local(),
+            (None, Some(zone)) => from_zone(&self.naive, zone)?,
+            (None, None) => self.naive
+        };
+        to_date(&local)
+    }
+}
+impl From<Date> for ZonedDate
+{
+    fn from(date: Date) -> Self
+    {
+        Self
+        {
+            naive: date.as_naive_datetime(),
+            offset: None
+        }
+    }
+}
+impl std::fmt::Display for ZonedDate
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        let naive = self.naive.format("%Y-%m-%dT%H:%M:%S");
+        match self.offset
+        {
+            Some(offset) => write!(f, "{}{}", naive, offset),
+            None => write!(f, "{}", naive)
+        }
+    }
+}
+
+pub fn to_date(naive: &NaiveDateTime) -> Option<Date>
+{
+    Date::parse(&naive.format("%Y-%m-%dT%H:%M:%S").to_string())
+}
+
+///wall time in the zone -> local time of this computer, time skipped by DST transition is moved an hour forward
+pub fn from_zone(naive: &NaiveDateTime, zone: Tz) -> Option<NaiveDateTime>
+{
+    let zoned = zone.from_local_datetime(naive).earliest()
+        .or_else(|| zone.from_local_datetime(&(*naive + TimeDelta::hours(1))).earliest())?;
+    Some(zoned.with_timezone(&Local).naive_local())
+}
+
+///local time of this computer -> wall time in the zone
+pub fn to_zone(naive: &NaiveDateTime, zone: Tz) -> Option<NaiveDateTime>
+{
+    let local = Local.from_local_datetime(naive).earliest()
+        .or_else(|| Local.from_local_datetime(&(*naive + TimeDelta::hours(1))).earliest())?;
+    Some(local.with_timezone(&zone).naive_local())
+}
+
+pub fn deserialize_timezone<'de, D>(deserializer: D) -> Result<Option<Tz>, D::Error>
+where
+    D: serde::de::Deserializer<'de>,
+{
+    let s: String = serde::de::Deserialize::deserialize(deserializer)?;
+    s.parse::<Tz>()
+        .map(Some)
+        .map_err(|_| serde::de::Error::custom(["Ошибка, часового пояса `", &s, "` не существует"].concat()))
+}
+
+pub fn serialize_timezone<S>(zone: &Option<Tz>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match zone
+    {
+        Some(zone) => serializer.serialize_str(zone.name()),
+        None => serializer.serialize_none()
+    }
+}